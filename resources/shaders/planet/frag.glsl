@@ -28,6 +28,16 @@ in float v_humidity;
 in float v_temperature;
 in float v_height;
 in float v_water;
+in float v_continental;
+in float v_thickness;
+in float v_crust_density;
+in float v_crust_age;
+in float v_swell;
+in float v_hazard;
+in float v_eruption;
+in float v_convergent;
+in float v_divergent;
+in float v_transform;
 
 //out to whatever this renders too
 out vec4 color;
@@ -35,6 +45,9 @@ out vec4 color;
 //uniforms
 uniform vec3 to_light;
 uniform int map_mode;
+uniform bool hotspot_overlay;
+uniform bool boundary_overlay;
+uniform bool eruption_overlay;
 
 //interpolates between three colors
 vec3 three_color(vec3 col_a,vec3 col_b,vec3 col_c,float interpolant){
@@ -70,6 +83,14 @@ void main() {
     float norm_humidity = v_humidity/100.0;
     //-50c should be 0.0 & 50c should be 1.0
     float norm_temperature = (v_temperature/100.0)+.5;
+    //0km should be 0.0 & 70km should be 1.0
+    float norm_thickness = clamp(v_thickness/70.0,0.0,1.0);
+    //2.6g/cm^3 should be 0.0 & 3.1g/cm^3 should be 1.0
+    float norm_density = clamp((v_crust_density-2.6)*2.0,0.0,1.0);
+    //0 should be 0.0 & 200 million years should be 1.0
+    float norm_age = clamp(v_crust_age/200.0,0.0,1.0);
+    //magnitude 5 should be 0.0 & magnitude 9.5 should be 1.0
+    float norm_hazard = clamp((v_hazard-5.0)/4.5,0.0,1.0);
 
     switch (map_mode){
         //natural
@@ -112,6 +133,58 @@ void main() {
         case 6:
             color = vec4(v_normal,1.0);
             break;
+        //crust type, sea floor darkening with age
+        case 7:
+            if(v_continental>0.5){
+                color = vec4(ROCK,1.0);
+            }
+            else{
+                color = vec4(mix(CYAN,WATER,min(v_crust_age/80.0,1.0)),1.0);
+            }
+            break;
+        //crust thickness
+        case 8:
+            color = vec4(three_color(BLUE,YELLOW,RED,norm_thickness),1.0);
+            break;
+        //crust density
+        case 9:
+            color = vec4(three_color(YELLOW,GREEN,BLUE,norm_density),1.0);
+            break;
+        //crust age, continents are much older than any sea floor so are left grey
+        case 10:
+            if(v_continental>0.5){
+                color = vec4(vec3(0.5),1.0);
+            }
+            else{
+                color = vec4(five_color(RED,YELLOW,GREEN,CYAN,BLUE,norm_age),1.0);
+            }
+            break;
+        //seismic hazard, from the strongest recent earthquake nearby, ground that hasnt shaken left grey
+        case 11:
+            if(v_hazard<1.0){
+                color = vec4(vec3(v_height>0.0 ? 0.6 : 0.3),1.0);
+            }
+            else{
+                color = vec4(three_color(GREEN,YELLOW,RED,norm_hazard),1.0);
+            }
+            break;
+    }
+
+    //plumes glow orange over whatever map is shown, fading along the trail they leave as it cools
+    if(hotspot_overlay){
+        color = vec4(mix(color.rgb,vec3(1.0,0.4,0.0),clamp(v_swell,0.0,1.0)*0.8),1.0);
+    }
+    //fresh eruptions glow red, brighter the more explosive, fading as they age
+    if(eruption_overlay){
+        color = vec4(mix(color.rgb,vec3(1.0,0.1,0.0),clamp(v_eruption/8.0,0.0,1.0)),1.0);
+    }
+    //convergent red, divergent blue, transform green
+    if(boundary_overlay){
+        float boundary = max(v_convergent,max(v_divergent,v_transform));
+        if(boundary>0.0){
+            vec3 boundary_color = (vec3(1.0,0.0,0.0)*v_convergent+vec3(0.0,0.3,1.0)*v_divergent+vec3(0.0,1.0,0.2)*v_transform)/(v_convergent+v_divergent+v_transform);
+            color = vec4(mix(color.rgb,boundary_color,boundary*0.8),1.0);
+        }
     }
 }
 
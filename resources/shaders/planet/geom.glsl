@@ -9,6 +9,16 @@ in VS_OUT {
     float temperature;
     float height;
     float water;
+    float continental;
+    float thickness;
+    float crust_density;
+    float crust_age;
+    float swell;
+    float hazard;
+    float eruption;
+    float convergent;
+    float divergent;
+    float transform;
 } gs_in[];
 
 //out for frag
@@ -17,6 +27,16 @@ out float v_humidity;
 out float v_temperature;
 out float v_height;
 out float v_water;
+out float v_continental;
+out float v_thickness;
+out float v_crust_density;
+out float v_crust_age;
+out float v_swell;
+out float v_hazard;
+out float v_eruption;
+out float v_convergent;
+out float v_divergent;
+out float v_transform;
 
 
 void main() {
@@ -39,6 +59,16 @@ void main() {
         v_temperature = gs_in[i].temperature;
         v_height = gs_in[i].height;
         v_water = gs_in[i].water;
+        v_continental = gs_in[i].continental;
+        v_thickness = gs_in[i].thickness;
+        v_crust_density = gs_in[i].crust_density;
+        v_crust_age = gs_in[i].crust_age;
+        v_swell = gs_in[i].swell;
+        v_hazard = gs_in[i].hazard;
+        v_eruption = gs_in[i].eruption;
+        v_convergent = gs_in[i].convergent;
+        v_divergent = gs_in[i].divergent;
+        v_transform = gs_in[i].transform;
         EmitVertex();
     }
     EndPrimitive();
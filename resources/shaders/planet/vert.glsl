@@ -9,6 +9,20 @@ in float humidity;
 in float temperature;
 in float water;
 
+//crust data in
+in float continental;
+in float thickness;
+in float crust_density;
+in float crust_age;
+in float swell;
+in float hazard;
+in float eruption;
+
+//boundary data in
+in float convergent;
+in float divergent;
+in float transform;
+
 //data for geometry shader
 out VS_OUT {
     vec3 pos;
@@ -16,6 +30,16 @@ out VS_OUT {
     float temperature;
     float height;
     float water;
+    float continental;
+    float thickness;
+    float crust_density;
+    float crust_age;
+    float swell;
+    float hazard;
+    float eruption;
+    float convergent;
+    float divergent;
+    float transform;
 } vs_out;
 
 //uniforms
@@ -28,6 +52,16 @@ void main() {
     vs_out.temperature = temperature;
     vs_out.height = height;
     vs_out.water = water;
+    vs_out.continental = continental;
+    vs_out.thickness = thickness;
+    vs_out.crust_density = crust_density;
+    vs_out.crust_age = crust_age;
+    vs_out.swell = swell;
+    vs_out.hazard = hazard;
+    vs_out.eruption = eruption;
+    vs_out.convergent = convergent;
+    vs_out.divergent = divergent;
+    vs_out.transform = transform;
 
     vec3 new_pos = position;
     if(height>0.0){
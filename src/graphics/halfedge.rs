@@ -0,0 +1,297 @@
+//connectivity aware triangle mesh, for when a shape has to be changed locally instead of rebuilt
+//each face is stored as 3 corners, and each corner is also the half edge leaving it, so next and prev never need storing
+//twins are found through the half edges leaving each vertex, which also lets meshes with holes or odd fans work
+//faces keep their slot for as long as they exist, and removed faces leave slots free for new ones
+//so changing part of the mesh only changes that part of the slots, e.g. for uploading to the gpu
+//vertices joined to each vertex are kept sorted and updated with every face, so finding neighbours never has to walk faces
+//only vertex indices are stored, positions are left to whatever owns the mesh
+use std::collections::BTreeSet;
+
+//internal modules
+use super::shapes::Shape;
+
+//marks corners of a face that has been removed
+const DEAD: u32 = u32::MAX;
+
+//triangles removed and added by a local operation, so anything tracking the mesh can follow along
+pub struct Change{
+    pub removed: Vec<u32>,
+    pub added: Vec<u32>,
+}
+
+pub struct HalfEdgeMesh{
+    //vertex at the start of each half edge, 3 per face slot, wound the same as the indices it was made from
+    corners: Vec<u32>,
+    //half edges leaving each vertex, lowest first
+    outgoing: Vec<Vec<u32>>,
+    //slots of removed faces before the last face in use, the lowest is filled first
+    free: BTreeSet<u32>,
+    //vertices sharing an edge with each vertex, lowest first
+    neighbours: Vec<Vec<u32>>,
+}
+impl HalfEdgeMesh{
+    //mesh of vertex_count vertices from a triangle index list
+    pub fn new(vertex_count:usize, indices:&[u32])->HalfEdgeMesh{
+        let mut mesh = HalfEdgeMesh {
+            corners: Vec::with_capacity(indices.len()),
+            outgoing: vec![Vec::new();vertex_count],
+            free: BTreeSet::new(),
+            neighbours: vec![Vec::new();vertex_count],
+        };
+        mesh.add_faces(indices);
+        mesh
+    }
+
+    //mesh with faces in the same slots as those given by slots(), so it carries on changing the same way
+    pub fn from_slots(vertex_count:usize, slots:&[u32])->HalfEdgeMesh{
+        let mut mesh = HalfEdgeMesh::new(vertex_count, &[]);
+        for (slot,tri) in slots.chunks(3).enumerate(){
+            if tri[0] == tri[1]{
+                mesh.corners.extend_from_slice(&[DEAD;3]);
+                mesh.free.insert(slot as u32);
+            }else{
+                mesh.add_face([tri[0],tri[1],tri[2]]);
+            }
+        }
+        mesh
+    }
+
+    pub fn from_shape(shape:&Shape)->HalfEdgeMesh{
+        HalfEdgeMesh::new(shape.vertices.len(), &shape.indices)
+    }
+
+    pub fn vertex_count(&self)->usize{
+        self.outgoing.len()
+    }
+
+    pub fn face_count(&self)->usize{
+        self.corners.len()/3-self.free.len()
+    }
+
+    //every face still in the mesh, in the order of their slots
+    pub fn faces(&self)->impl Iterator<Item = [u32;3]> + '_{
+        self.corners.chunks(3)
+            .filter(|t| t[0] != DEAD)
+            .map(|t| [t[0],t[1],t[2]])
+    }
+
+    //faces as an index list
+    pub fn indices(&self)->Vec<u32>{
+        self.faces().flatten().collect()
+    }
+
+    //every face slot as an index list, e.g. for the gpu, with free slots as triangles of one corner that draw nothing
+    pub fn slots(&self)->Vec<u32>{
+        self.corners.iter().map(|c| if *c == DEAD {0} else {*c}).collect()
+    }
+
+    //half edge navigation
+    pub fn origin(&self, edge:u32)->u32{
+        self.corners[edge as usize]
+    }
+    pub fn next(&self, edge:u32)->u32{
+        edge-edge%3+(edge+1)%3
+    }
+    pub fn prev(&self, edge:u32)->u32{
+        edge-edge%3+(edge+2)%3
+    }
+    pub fn dest(&self, edge:u32)->u32{
+        self.origin(self.next(edge))
+    }
+    //corners of the face a half edge belongs to, starting from the edge
+    pub fn face(&self, edge:u32)->[u32;3]{
+        [self.origin(edge),self.dest(edge),self.origin(self.prev(edge))]
+    }
+
+    //half edge going from a to b, if there is one
+    pub fn find_edge(&self, a:u32, b:u32)->Option<u32>{
+        self.outgoing[a as usize].iter()
+            .copied()
+            .find(|e| self.dest(*e) == b)
+    }
+
+    //half edge going the other way along the same edge, none if the edge is on the border of a hole
+    pub fn twin(&self, edge:u32)->Option<u32>{
+        self.find_edge(self.dest(edge), self.origin(edge))
+    }
+
+    //third point of the face on the left of a to b
+    pub fn opposite(&self, a:u32, b:u32)->Option<u32>{
+        self.find_edge(a,b).map(|e| self.origin(self.prev(e)))
+    }
+
+    //half edge starting a face with the given corners, allowing for it to start from any of them
+    fn find_face(&self, tri:[u32;3])->Option<u32>{
+        self.outgoing[tri[0] as usize].iter()
+            .copied()
+            .find(|e| self.dest(*e) == tri[1] && self.origin(self.prev(*e)) == tri[2])
+    }
+
+    //faces touching a vertex, each starting from it
+    pub fn vertex_faces(&self, vertex:u32)->impl Iterator<Item = [u32;3]> + '_{
+        self.outgoing[vertex as usize].iter().map(|e| self.face(*e))
+    }
+
+    //vertices connected to a vertex, lowest first, including ones only reached across a hole
+    pub fn neighbours(&self, vertex:u32)->&[u32]{
+        &self.neighbours[vertex as usize]
+    }
+
+    //whether two vertices share an edge
+    pub fn is_edge(&self, a:u32, b:u32)->bool{
+        self.neighbours[a as usize].binary_search(&b).is_ok()
+    }
+
+    //makes space for a new vertex, returning its index
+    pub fn add_vertex(&mut self)->u32{
+        self.outgoing.push(Vec::new());
+        self.neighbours.push(Vec::new());
+        (self.outgoing.len()-1) as u32
+    }
+
+    //records an edge between two vertices, if it isnt already
+    fn link(&mut self, a:u32, b:u32){
+        for (from,to) in [(a,b),(b,a)]{
+            let neighbours = &mut self.neighbours[from as usize];
+            if let Err(pos) = neighbours.binary_search(&to){
+                neighbours.insert(pos, to);
+            }
+        }
+    }
+
+    //forgets an edge between two vertices, once no face uses it in either direction
+    fn unlink(&mut self, a:u32, b:u32){
+        if self.find_edge(a,b).is_some() || self.find_edge(b,a).is_some(){
+            return;
+        }
+        for (from,to) in [(a,b),(b,a)]{
+            let neighbours = &mut self.neighbours[from as usize];
+            if let Ok(pos) = neighbours.binary_search(&to){
+                neighbours.remove(pos);
+            }
+        }
+    }
+
+    pub fn add_face(&mut self, tri:[u32;3]){
+        let start = match self.free.pop_first(){
+            Some(slot)=> slot*3,
+            None=> {
+                self.corners.extend_from_slice(&[DEAD;3]);
+                (self.corners.len()-3) as u32
+            },
+        };
+        for (i,vertex) in tri.iter().enumerate(){
+            let edge = start+i as u32;
+            self.corners[edge as usize] = *vertex;
+            let outgoing = &mut self.outgoing[*vertex as usize];
+            let pos = outgoing.binary_search(&edge).unwrap_or_else(|pos| pos);
+            outgoing.insert(pos, edge);
+        }
+        for i in 0..3{
+            self.link(tri[i], tri[(i+1)%3]);
+        }
+    }
+
+    pub fn add_faces(&mut self, indices:&[u32]){
+        for tri in indices.chunks(3){
+            self.add_face([tri[0],tri[1],tri[2]]);
+        }
+    }
+
+    //removes face with the given corners, returns whether it was there to remove
+    pub fn remove_face(&mut self, tri:[u32;3])->bool{
+        let start = match self.find_face(tri){
+            Some(edge)=> edge-edge%3,
+            None=> return false,
+        };
+        for edge in start..start+3{
+            let vertex = self.corners[edge as usize];
+            self.outgoing[vertex as usize].retain(|e| *e != edge);
+            self.corners[edge as usize] = DEAD;
+        }
+        for i in 0..3{
+            self.unlink(tri[i], tri[(i+1)%3]);
+        }
+        self.free.insert(start/3);
+        //free slots at the end are dropped, so the slots only go as far as the last face
+        while self.corners.last() == Some(&DEAD){
+            self.corners.truncate(self.corners.len()-3);
+            self.free.remove(&(self.corners.len() as u32/3));
+        }
+        true
+    }
+
+    pub fn remove_faces(&mut self, indices:&[u32]){
+        for tri in indices.chunks(3){
+            self.remove_face([tri[0],tri[1],tri[2]]);
+        }
+    }
+
+    //faces that would fill the hole left by a vertex by fanning out from apex, which should be one of its neighbours
+    //only lies flat if every corner of the hole can be seen from the apex, which the mesh cant tell without positions
+    pub fn fan(&self, vertex:u32, apex:u32)->Vec<u32>{
+        //each face around the vertex leaves behind the edge opposite it, which gets joined to the apex instead
+        //edges already touching the apex would only make slivers
+        self.vertex_faces(vertex)
+            .filter(|t| t[1] != apex && t[2] != apex)
+            .flat_map(|t| [t[1],t[2],apex])
+            .collect()
+    }
+
+    //removes a vertex and fills the hole it leaves with the given faces, e.g. from fan
+    pub fn remove_vertex(&mut self, vertex:u32, fill:&[u32])->Change{
+        let removed:Vec<u32> = self.vertex_faces(vertex).flatten().collect();
+        let added = fill.to_vec();
+        self.remove_faces(&removed);
+        self.add_faces(&added);
+        Change { removed, added }
+    }
+
+    //swaps the edge between two faces for one between their other corners
+    //none if the edge is on a hole, or if the new edge already exists
+    pub fn flip_edge(&mut self, a:u32, b:u32)->Option<Change>{
+        let c = self.opposite(a,b)?;
+        let d = self.opposite(b,a)?;
+        if c == d || self.find_edge(c,d).is_some() || self.find_edge(d,c).is_some(){
+            return None;
+        }
+        let removed = vec![a,b,c, b,a,d];
+        let added = vec![c,a,d, d,b,c];
+        self.remove_faces(&removed);
+        self.add_faces(&added);
+        Some(Change { removed, added })
+    }
+
+    //puts a new vertex partway along an edge, splitting the faces either side of it
+    //none if no face uses the edge
+    pub fn split_edge(&mut self, a:u32, b:u32, new:u32)->Option<Change>{
+        let mut removed:Vec<u32> = Vec::with_capacity(6);
+        let mut added:Vec<u32> = Vec::with_capacity(12);
+        for (from,to) in [(a,b),(b,a)]{
+            if let Some(c) = self.opposite(from,to){
+                removed.extend_from_slice(&[from,to,c]);
+                added.extend_from_slice(&[from,new,c, new,to,c]);
+            }
+        }
+        if removed.is_empty(){
+            return None;
+        }
+        self.remove_faces(&removed);
+        self.add_faces(&added);
+        Some(Change { removed, added })
+    }
+
+    //puts a new vertex inside a face, connecting it to every corner
+    //none if the face isnt in the mesh
+    pub fn insert_vertex(&mut self, tri:[u32;3], new:u32)->Option<Change>{
+        if !self.remove_face(tri){
+            return None;
+        }
+        let added = vec![
+            tri[0],tri[1],new,
+            tri[1],tri[2],new,
+            tri[2],tri[0],new];
+        self.add_faces(&added);
+        Some(Change { removed: tri.to_vec(), added })
+    }
+}
@@ -3,6 +3,8 @@ use nalgebra_glm as glm;
 
 //all the child modules
 pub mod shapes;
+pub mod halfedge;
+pub mod render;
 
 pub struct Camera{
     //matrices
@@ -0,0 +1,194 @@
+//renders a planet, kept seperate from the simulation so the sim can run without a display
+//drawn from snapshots of the sim rather than the planet itself, so the sim can run on another thread
+use std::ops::Range;
+
+//external crates
+use glium::Surface;
+use nalgebra_glm as glm;
+
+//internal modules
+use crate::graphics::Camera;
+use crate::planet::{boundary::BoundaryData,crust::CrustData,surface::CellData,worker::Snapshot};
+
+//changed elements closer than this are uploaded together, as a few extra elements cost less than another upload
+const MERGE_GAP: usize = 64;
+//index buffer is made this much bigger than the mesh needs, so it can grow a while before the buffer has to be remade
+const INDEX_HEADROOM: f32 = 1.5;
+
+#[derive(PartialEq)]
+#[derive(Debug)]
+pub enum LightPosition{
+    Sun,
+    Camera,
+    Fixed
+}
+
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum MapMode{
+    Natural,
+    Height,
+    Temperature,
+    Humidity,
+    Water,
+    Relief,
+    Normals,
+    Crust,
+    CrustThickness,
+    CrustDensity,
+    CrustAge,
+    SeismicHazard,
+}
+
+//struct containing all things needed passed to the gpu
+pub struct RenderData{
+    //buffer containing cell data needed for rendering
+    planet_data: glium::VertexBuffer<CellData>,
+    //crust under each cell, drawn alongside planet_data
+    crust_data: glium::VertexBuffer<CrustData>,
+    //plate boundaries each cell is on, also drawn alongside planet_data
+    boundary_data: glium::VertexBuffer<BoundaryData>,
+    //indices, define triangles of planet, only the start of it is in use
+    indices: glium::IndexBuffer<u32>,
+    //copies of what is in the buffers, to find what has changed in each snapshot
+    cells: Vec<CellData>,
+    crust: Vec<CrustData>,
+    boundaries: Vec<BoundaryData>,
+    triangles: Vec<u32>,
+    //how exagerated the planet surface will be
+    pub scale: f32,
+    //where the light source is
+    pub light_pos: LightPosition,
+    //map mode to use when displaying the planet
+    pub map_mode: MapMode,
+    //whether heat from mantle plumes is drawn over the map
+    pub hotspot_overlay: bool,
+    //whether plate boundaries are drawn over the map, coloured by type
+    pub boundary_overlay: bool,
+    //whether recent volcanic eruptions are drawn over the map
+    pub eruption_overlay: bool,
+    //direction of the sun in the last snapshot synced
+    to_sun: glm::Vec3,
+}
+impl RenderData{
+    pub fn new(display:&glium::Display, snapshot:&Snapshot)->RenderData{
+        RenderData{
+            //dynamic as this will change frequently
+            planet_data: glium::VertexBuffer::dynamic(display, &snapshot.cells).unwrap(),
+
+            crust_data: glium::VertexBuffer::dynamic(display, &snapshot.crust).unwrap(),
+
+            boundary_data: glium::VertexBuffer::dynamic(display, &snapshot.boundaries).unwrap(),
+
+            //dynamic as well, as the mesh changes each retriangulation
+            indices: index_buffer(display, &snapshot.triangles),
+
+            cells: snapshot.cells.clone(),
+
+            crust: snapshot.crust.clone(),
+
+            boundaries: snapshot.boundaries.clone(),
+
+            triangles: snapshot.triangles.clone(),
+
+            scale: 0.01,
+
+            light_pos: LightPosition::Fixed,
+
+            map_mode: MapMode::Natural,
+
+            hotspot_overlay: false,
+
+            boundary_overlay: false,
+
+            eruption_overlay: false,
+
+            to_sun: snapshot.sim_info.to_sun,
+        }
+    }
+
+    //copies a snapshot of the simulation into the gpu buffers, only uploading what changed since the last one
+    pub fn sync(&mut self, display:&glium::Display, snapshot:&Snapshot){
+        //vertex buffers are only remade if their size has changed e.g. from loading
+        sync_vertices(display, &mut self.planet_data, &mut self.cells, &snapshot.cells);
+        sync_vertices(display, &mut self.crust_data, &mut self.crust, &snapshot.crust);
+        sync_vertices(display, &mut self.boundary_data, &mut self.boundaries, &snapshot.boundaries);
+        //faces keep their slots, so only the ones that changed and any new slots on the end need uploading
+        let new = &snapshot.triangles;
+        if new.len() > self.indices.len(){
+            self.indices = index_buffer(display, new);
+        }else{
+            let kept = self.triangles.len().min(new.len());
+            for range in dirty_ranges(&self.triangles[..kept], &new[..kept]){
+                self.indices.slice_mut(range.clone()).unwrap().write(&new[range]);
+            }
+            if new.len() > kept{
+                self.indices.slice_mut(kept..new.len()).unwrap().write(&new[kept..]);
+            }
+        }
+        self.triangles.clone_from(new);
+        self.to_sun = snapshot.sim_info.to_sun;
+    }
+
+    pub fn draw(&self, target:&mut glium::Frame, program:&glium::Program, params:&glium::DrawParameters,cam:&Camera){
+        //turn matrices into type gpu will take
+        let pers:[[f32;4];4] = cam.perspective.into();
+        let view:[[f32;4];4] = cam.view.into();
+        //send light pos corrosponding to user choice
+        let to_light:[f32;3] = match self.light_pos{
+            LightPosition::Sun=> self.to_sun.into(),
+            LightPosition::Camera=> cam.pos.normalize().into(),
+            LightPosition::Fixed=> [0.0,0.0,1.0],
+        };
+
+        let uniform = glium::uniform!{
+            perspective:pers,
+            view: view,
+            to_light: to_light,
+            terra_scale: self.scale,
+            map_mode: self.map_mode as i32,
+            hotspot_overlay: self.hotspot_overlay,
+            boundary_overlay: self.boundary_overlay,
+            eruption_overlay: self.eruption_overlay,
+        };
+
+        let in_use = self.indices.slice(0..self.triangles.len()).unwrap();
+        target.draw((&self.planet_data,&self.crust_data,&self.boundary_data),in_use,program,&uniform,params).unwrap();
+    }
+}
+
+//index buffer with room for the mesh to grow, holding the given indices at its start
+fn index_buffer(display:&glium::Display, triangles:&[u32])->glium::IndexBuffer<u32>{
+    //whole triangles only
+    let capacity = (triangles.len() as f32*INDEX_HEADROOM) as usize/3*3;
+    let mut buffer = glium::IndexBuffer::empty_dynamic(display, glium::index::PrimitiveType::TrianglesList, capacity.max(3)).unwrap();
+    buffer.slice_mut(0..triangles.len()).unwrap().write(triangles);
+    buffer
+}
+
+//uploads whatever differs between a vertex buffer and the new vertices, keeping the copy of the buffer in step
+fn sync_vertices<T:glium::Vertex+PartialEq>(display:&glium::Display, buffer:&mut glium::VertexBuffer<T>, copy:&mut Vec<T>, new:&[T]){
+    if copy.len() == new.len(){
+        for range in dirty_ranges(copy, new){
+            buffer.slice_mut(range.clone()).unwrap().write(&new[range.clone()]);
+            copy[range.clone()].copy_from_slice(&new[range]);
+        }
+    }else{
+        *buffer = glium::VertexBuffer::dynamic(display, new).unwrap();
+        *copy = new.to_vec();
+    }
+}
+
+//ranges of elements that differ between two lists of the same length
+//ranges less than MERGE_GAP apart are joined into one
+pub fn dirty_ranges<T:PartialEq>(old:&[T], new:&[T])->Vec<Range<usize>>{
+    let mut ranges:Vec<Range<usize>> = Vec::new();
+    for i in (0..new.len()).filter(|i| old[*i] != new[*i]){
+        match ranges.last_mut(){
+            Some(last) if i-last.end < MERGE_GAP=> last.end = i+1,
+            _=> ranges.push(i..i+1),
+        }
+    }
+    ranges
+}
@@ -1,6 +1,6 @@
 //just a bunch of default shapes and operations that can be done one them
 use nalgebra_glm as glm;
-use std::collections::{HashMap,HashSet};
+use std::collections::HashMap;
 
 //used to just flip a tuple so two containing the same numbers are always identical, used in the hashmap during subdivision
 fn order_edge(a:u32,b:u32)->(u32,u32){
@@ -60,10 +60,10 @@ impl Shape{
                     let edge = order_edge(tri[i],tri[(i+1)%3]);
 
                     //if edge isnt in dictionary, calculate midpoint, add to vertices, store index in dictionary
-                    if midpoints.get(&edge).is_none() {
+                    if let std::collections::hash_map::Entry::Vacant(entry) = midpoints.entry(edge) {
                         let mid = (self.vertices[edge.0 as usize]+self.vertices[edge.1 as usize])*0.5;
                         self.vertices.push(mid);//adds midpoint as vertex
-                        midpoints.insert(edge, u32::try_from(self.vertices.len()-1).expect("More vertices than datatype can represent"));
+                        entry.insert(u32::try_from(self.vertices.len()-1).expect("More vertices than datatype can represent"));
                     }
                 }
 
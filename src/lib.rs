@@ -0,0 +1,22 @@
+//library side of the sim, everything in planet can be driven without a window or gl context
+//the binary in main.rs only handles the window, input and drawing
+
+//internal modules used
+use graphics::{Camera,render::RenderData};
+use planet::worker::SimWorker;
+
+//child modules
+#[cfg(test)]
+mod tests;
+pub mod planet;
+pub mod graphics;
+pub mod menus;
+
+//enum discribing the games current state, containing data specific to each
+//only one ever exists, so the size difference between variants doesnt matter
+#[allow(clippy::large_enum_variant)]
+pub enum GameState{
+    //settings for the new planet, and which info the menu is showing about them
+    Generate(planet::GenInfo,menus::MenuState),
+    Playing(SimWorker,RenderData,Camera),
+}
@@ -1,29 +1,14 @@
 //external crates
-use glium::{Surface,glutin::{self, event::MouseButton, dpi::{PhysicalPosition, PhysicalSize}}};
+use glium::{Surface,glutin::{self, event::MouseButton, dpi::PhysicalSize}};
 use nalgebra_glm as glm;
-use std::time::{Duration, Instant};
 
 //internal modules used
-use graphics::Camera;
-use planet::{Planet, GenInfo};
-
-//child modules
-#[cfg(test)]
-mod tests;
-mod planet;
-mod graphics;
-mod menus;
-
-//enum discribing the games current state, containing data specific to each
-pub enum GameState{
-    Generate(planet::GenInfo),
-    Playing(Planet,Camera),
-}
+use untitled_planet_sim::{GameState,graphics::{self,render::RenderData},menus,planet::{self,Planet,worker::{SimWorker,Snapshot}}};
 
 
 fn main() {
     //handles window and device events
-    let mut event_loop = glutin::event_loop::EventLoop::new();
+    let event_loop = glutin::event_loop::EventLoop::new();
     //window specific
     let wb = glutin::window::WindowBuilder::new()
         .with_inner_size(glium::glutin::dpi::LogicalSize::new(800.0, 450.0))
@@ -50,8 +35,6 @@ fn main() {
         .. Default::default()
     };
 
-    //set time of start of frame
-    let mut frame_time = Instant::now();
     //pos of mouse if middle click pressed previous frame, if wasnt in last frame None is stored
     let mut drag_last:Option<glm::Vec2> = None;
     //mouse position as screen coords with top left of screen being -1,-1 and bottom right being 1,1
@@ -71,17 +54,18 @@ fn main() {
 
     //default settings for planet gen
     let default_gen = planet::GenInfo{
-        menu_state: menus::MenuState::Intro,
         iterations: 5,
         seed: 1,
         plate_no: 2,
+        hotspot_no: 5,
+        hotspot_strength: 2.0,
         axial_tilt: 23.0/180.0,
         lapse_rate:9.8,
         base_temp: 30.0
     };
 
     //set starting game state as generating the planet
-    let mut game_state = GameState::Generate(default_gen);
+    let mut game_state = GameState::Generate(default_gen, menus::MenuState::Intro);
 
     //loop forever until close event
     event_loop.run(move |event, _, control_flow| {
@@ -94,28 +78,27 @@ fn main() {
         if let glutin::event::Event::WindowEvent { event, .. } = event{
             //check game state for the events handled differently based on such
             //if generating planet
-            if let GameState::Generate(ref gen) = game_state{
+            if let GameState::Generate(ref gen,_) = game_state{
                 //if key pressed 
                 if let glutin::event::WindowEvent::KeyboardInput { device_id:_, input, is_synthetic:_ }=event{
-                    match input.virtual_keycode{
-                        Some(glutin::event::VirtualKeyCode::Return)=> {
-                            //creates new camera
-                            let dimensions = display.get_framebuffer_dimensions();
-                            let cam = graphics::Camera::new(dimensions.0 as f32/dimensions.1 as f32, 
-                                glm::vec3(0.0,0.0,5.0), 
-                                glm::Vec3::zeros(),
-                                glm::Vec3::y());
-
-                            let planet = planet::Planet::new(&display, &gen);
-                            game_state= GameState::Playing(planet, cam)},
-                        _=>()
+                    if let Some(glutin::event::VirtualKeyCode::Return) = input.virtual_keycode{
+                        //creates new camera
+                        let dimensions = display.get_framebuffer_dimensions();
+                        let cam = graphics::Camera::new(dimensions.0 as f32/dimensions.1 as f32, 
+                            glm::vec3(0.0,0.0,5.0), 
+                            glm::Vec3::zeros(),
+                            glm::Vec3::y());
+
+                        let planet = Planet::new(gen);
+                        let render_data = RenderData::new(&display, &Snapshot::new(&planet, false));
+                        game_state= GameState::Playing(SimWorker::spawn(planet), render_data, cam);
                     }
                 }
             }
             //if running sim
-            else if let GameState::Playing(_,ref mut cam) = game_state{
+            else if let GameState::Playing(_,_,ref mut cam) = game_state{
                 //if mouse wheel scrolled, change camera accordingly
-                if let glutin::event::WindowEvent::MouseWheel { device_id:_, delta, phase:_, modifiers:_ } = event{
+                if let glutin::event::WindowEvent::MouseWheel { delta, .. } = event{
                     if let glutin::event::MouseScrollDelta::LineDelta(_,y) = delta{
                         //zoom 5% according to direction scrolled
                         cam.pos *= 1.0+(y*0.05);
@@ -126,12 +109,12 @@ fn main() {
                     }
                 }
                 //if mouse moves, record new pos 
-                else if let glutin::event::WindowEvent::CursorMoved { device_id:_, position, modifiers:_ } = event{
+                else if let glutin::event::WindowEvent::CursorMoved { position, .. } = event{
                     //mouse pos but as uv coords, top left of screen being the origin
                     mouse_pos = glm::vec2(position.x as f32/window_res.width as f32, position.y as f32/window_res.height as f32);
                 }
                 //if mouse input
-                else if let glutin::event::WindowEvent::MouseInput { device_id:_, state, button, modifiers:_  } = event{
+                else if let glutin::event::WindowEvent::MouseInput { state, button, .. } = event{
                     //if mid or right button pressed
                     if (button == MouseButton::Middle||button == MouseButton::Right) && state == glutin::event::ElementState::Pressed{
                         //record current mouse pos
@@ -183,27 +166,23 @@ fn main() {
 
         //once window events handled, run main thing
         else if let glutin::event::Event::MainEventsCleared = event{
-            //reset the delta_time at the start of frame
-            let delta_time = frame_time.elapsed().as_secs_f32();
-            frame_time = Instant::now();
-
             //creates buffer to store image in before drawing to window
             let mut target = display.draw();
             //clears buffer for colors and depth
             target.clear_color_and_depth((0.0, 0.0, 0.0, 1.0), 1.0);
 
             //generating planet
-            if let GameState::Generate(ref gen_info) = game_state{
+            if let GameState::Generate(..) = game_state{
                 //handles egui input and what results from it
                 egui_glium.run(&display, |egui_ctx| {
                     menus::planet_create(egui_ctx, &display,&mut game_state);
                 });
             }
             //sim running
-            else if let GameState::Playing(ref mut planet,ref mut camera) = game_state{
+            else if let GameState::Playing(ref mut worker,ref mut render_data,ref mut camera) = game_state{
                 //handles egui input and what results from it
                 egui_glium.run(&display, |egui_ctx| {
-                    menus::playing(egui_ctx,&mut params, planet)
+                    menus::playing(egui_ctx,&mut params, worker, render_data)
                 });
 
                 //rotate camera based on how dragged
@@ -212,8 +191,8 @@ fn main() {
                     let drag = mouse_pos-last_pos;
                     let normal = glm::cross(&camera.pos,&glm::Vec3::y());
                     //rotate camera around origin using drag
-                    camera.pos = glm::rotate_vec3(&camera.pos, drag.y*3.14159, &normal);
-                    camera.pos = glm::rotate_y_vec3(&camera.pos, -drag.x*3.14159);
+                    camera.pos = glm::rotate_vec3(&camera.pos, drag.y*std::f32::consts::PI, &normal);
+                    camera.pos = glm::rotate_y_vec3(&camera.pos, -drag.x*std::f32::consts::PI);
                     //record current pos as new
                     drag_last = Some(mouse_pos);
                 }
@@ -222,10 +201,13 @@ fn main() {
                 camera.update_view();
                 
                 //updates planet with the specification of how many days pass per frame
-                planet.update(delta_time, &display);//quarter year per second, placeholder
+                //copy new state of planet over to the gpu, if the sim thread has published one
+                if let Some(snapshot) = worker.poll(){
+                    render_data.sync(&display, &snapshot);
+                }
 
                 //draw planet
-                planet.draw(&mut target, &planet_shader, &params, &camera);
+                render_data.draw(&mut target, &planet_shader, &params, camera);
                 //planet.draw(&mut target, &map_shader, &params, &camera);
             }
             
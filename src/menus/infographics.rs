@@ -2,7 +2,7 @@
 use core::ops::Range;
 use std::f64;
 //external crates used
-use egui::{Context,plot::{Line, Plot, PlotPoints,VLine, Polygon, Legend, Corner, self}, Color32};
+use egui::{Context,plot::{Line, Plot, PlotPoints,VLine, Polygon, Legend, Corner}, Color32};
 use nalgebra_glm as glm;
 
 use crate::planet::{GenInfo, utils::*};
@@ -28,7 +28,6 @@ fn subdivided_tri_lines(iterations:u8)->Vec<Line>{
         .into_iter()
         .map(|line| {
             let new_line:Vec<glm::Vec2> =(0..point_number)
-                .into_iter()
                 .map(|point|{
                     //percentage along line the point being added is
                     let line_percentage = step_size*point as f32;
@@ -93,11 +92,6 @@ fn plot_arc(pos:[f64;2],radius:f64,start:f64,end:f64)->Line{
     Line::new(arc)
 }
 
-//creates line rotated about a point
-fn plot_line(pos:[f64;2],size:f64,rotation:f64){
-    
-}
-
 //creates circle given pos and radius
 fn plot_circle(pos:[f64;2],radius:f64)->Polygon{
     let step_size = std::f64::consts::PI/100.0;
@@ -291,7 +285,42 @@ pub fn plate_info(egui_ctx: &Context, gen_info: &GenInfo){
         });
 }
 
-pub fn lapse_rate_info(egui_ctx: &Context){
+//hotspot menu showing each hotspot as a plume under the surface, wider for stronger ones
+pub fn hotspot_info(egui_ctx: &Context, gen_info: &GenInfo){
+    egui::CentralPanel::default()
+        .show(egui_ctx, |ui| {
+            ui.heading("Hotspot Info");
+            ui.separator();
+            ui.label("Hotspots are plumes of hot mantle that stay still while plates move over them. They build volcanoes on a swell of raised crust, which sink as they are carried away, leaving chains of islands and seamounts. Strength is how many km of rock a hotspot builds every million years, each one varying around it. Here each hotspot is shown as a plume under the surface.");
+            ui.separator();
+
+            Plot::new("hotspot diagram")
+            .data_aspect(1.0)
+            .allow_scroll(false)
+            .allow_zoom(false)
+            .allow_drag(false)
+            .allow_boxed_zoom(false)
+            .show_axes([false;2])
+            .show_background(false)
+            .show_y(false)
+            .show_x(false)
+            .show(ui, |plot_ui| {
+                //surface as a line, with plumes spread evenly below it
+                plot_ui.line(Line::new(vec![[-1.0,0.0],[1.0,0.0]])
+                    .color(Color32::LIGHT_GREEN));
+                let spacing = 2.0/(gen_info.hotspot_no as f64+1.0);
+                let radius = (gen_info.hotspot_strength as f64/10.0*spacing*0.5).max(0.01);
+                for i in 1..=gen_info.hotspot_no{
+                    let x = -1.0+spacing*i as f64;
+                    //circle positions are in multiples of its radius
+                    plot_ui.polygon(plot_circle([x/radius,-0.5/radius], radius)
+                        .color(Color32::from_rgb(255, 100, 0)));
+                }
+            });
+        });
+}
+
+pub fn lapse_rate_info(_egui_ctx: &Context){
     
 }
 
@@ -320,7 +349,7 @@ pub fn base_temp_info(egui_ctx: &Context, gen_info: &GenInfo){
                 let base = Polygon::new(PlotPoints::new(
                     test_poly.iter()
                     .map(|p| {
-                        [test_points[*p as usize].x as f64,test_points[*p as usize].y as f64]
+                        [test_points[*p].x as f64,test_points[*p].y as f64]
                     })
                     .collect()));
 
@@ -1,16 +1,19 @@
-use std::{ops::Range, vec};
+use std::path::Path;
 
 //external crates
-use egui::{Context};
+use egui::Context;
 use glium::{Display,DrawParameters};
 use nalgebra_glm as glm;
 
 //internal modules
-use crate::{GenInfo, GameState,planet, graphics};
+use crate::{GameState,planet::{self,events::EventKind,export::{mesh::MeshFormat,raster::RasterFormat},worker::{Command,SimWorker,Snapshot}}, graphics::{self,render::{self,RenderData}}};
 
 //child modules
 mod infographics;
 
+//newest events listed in the playing menu
+const EVENTS_SHOWN: usize = 20;
+
 //enum for what the menu is showing right now
 pub enum MenuState{
     //introductory information being shown on menu
@@ -19,6 +22,8 @@ pub enum MenuState{
     Subdivision,
     //shows amount of plates as divisions on circle
     Plates,
+    //shows hotspots as circles sized by their strength
+    Hotspots,
     //menu showing circle and line representing axis
     AxialTilt,
     //displays the lapse rate, the rate at which temp drops with hight
@@ -27,15 +32,59 @@ pub enum MenuState{
     BaseTemp
 }
 
+//gets a value kept in egui's memory between frames, so menus can have state without it being in the sim
+fn ui_state<T: 'static + Clone + Send + Sync>(ui:&mut egui::Ui, name:&str, default:T)->T{
+    ui.data().get_temp_mut_or(egui::Id::new(name), default).clone()
+}
+
+//stores a value in egui's memory for the next frame
+fn set_ui_state<T: 'static + Clone + Send + Sync>(ui:&mut egui::Ui, name:&str, value:T){
+    ui.data().insert_temp(egui::Id::new(name), value);
+}
+
+//text box for a file path, kept in egui's memory so its shared between menus
+fn path_edit(ui:&mut egui::Ui, name:&str, default:&str)->String{
+    let mut path = ui_state(ui, name, String::from(default));
+    ui.text_edit_singleline(&mut path);
+    set_ui_state(ui, name, path.clone());
+    path
+}
+
+//shows the outcome of the last file operation, if any, and replaces it with a new one if given
+fn file_status(ui:&mut egui::Ui, new_status:Option<String>){
+    let id = egui::Id::new("file status");
+    if let Some(status) = new_status{
+        ui.data().insert_temp(id, status);
+    }
+    let status = ui.data().get_temp::<String>(id);
+    if let Some(status) = status{
+        ui.label(status);
+    }
+}
+
+//sends a setting to the sim if its control was changed
+//only that setting is sent, as the rest shown may be from a snapshot older than changes the sim already has
+fn send_setting<T:Send+'static>(worker:&SimWorker, changed:bool, value:T, set:fn(&mut planet::Planet,T)){
+    if changed{
+        worker.edit(move |planet| {
+            set(planet, value);
+            None
+        });
+    }
+}
+
 //menu for planet creation
 pub fn planet_create(egui_ctx: &Context,display: &Display,game_state: &mut GameState){
     
     let mut new_planet = false;
+    let mut loaded_planet = None;
     let gen_info;
+    let menu_state;
 
     //makes sure game state is the intended one for this menu
-    if let GameState::Generate(ref mut gen)=  game_state{
+    if let GameState::Generate(ref mut gen,ref mut state)=  game_state{
         gen_info=gen;
+        menu_state=state;
     }else{
         return;
     }
@@ -44,27 +93,37 @@ pub fn planet_create(egui_ctx: &Context,display: &Display,game_state: &mut GameS
         .show(egui_ctx,|ui| {
             ui.label("Shape Subdivisions");
             if ui.add(egui::Slider::new(&mut gen_info.iterations, 0..=7)).changed(){
-                gen_info.menu_state = MenuState::Subdivision;
+                *menu_state = MenuState::Subdivision;
             }
 
             ui.label("Plate Amount");
             if ui.add(egui::Slider::new(&mut gen_info.plate_no, 1..=100)).changed(){
-                gen_info.menu_state = MenuState::Plates;
+                *menu_state = MenuState::Plates;
+            }
+
+            ui.label("Hotspot Amount");
+            if ui.add(egui::Slider::new(&mut gen_info.hotspot_no, 0..=32)).changed(){
+                *menu_state = MenuState::Hotspots;
+            }
+
+            ui.label("Hotspot Strength (km/Myr)");
+            if ui.add(egui::Slider::new(&mut gen_info.hotspot_strength, 0.0..=10.0)).changed(){
+                *menu_state = MenuState::Hotspots;
             }
 
             ui.label("Axial Tilt");
             if ui.add(egui::Slider::new(&mut gen_info.axial_tilt, -1.0..=1.0)).changed(){
-                gen_info.menu_state = MenuState::AxialTilt;
+                *menu_state = MenuState::AxialTilt;
             }
 
             ui.label("Lapse Rate");
             if ui.add(egui::Slider::new(&mut gen_info.lapse_rate, 0.0..=25.0)).changed(){
-                gen_info.menu_state = MenuState::LapseRate;
+                *menu_state = MenuState::LapseRate;
             }
 
             ui.label("Base Temperature");
             if ui.add(egui::Slider::new(&mut gen_info.base_temp, -100.0..=100.0)).changed(){
-                gen_info.menu_state = MenuState::BaseTemp;
+                *menu_state = MenuState::BaseTemp;
             }
 
             ui.label("Seed");
@@ -74,56 +133,145 @@ pub fn planet_create(egui_ctx: &Context,display: &Display,game_state: &mut GameS
             if ui.button("CREATE").clicked(){
                 new_planet = true;
             }
+
+            ui.label("Load Existing Planet");
+            let path = path_edit(ui, "save path", "planet.ups");
+            let mut status = None;
+            if ui.button("LOAD").clicked(){
+                match planet::Planet::load(&path){
+                    Ok(planet)=> loaded_planet = Some(planet),
+                    Err(e)=> status = Some(format!("Failed to load: {}", e)),
+                }
+            }
+            file_status(ui, status);
         });
     
     //do menus based on menu state
-    match &gen_info.menu_state {
+    match menu_state {
         MenuState::Intro => infographics::intro_info(egui_ctx),
         MenuState::Subdivision => infographics::subdivision_info(egui_ctx,gen_info),
         MenuState::Plates=> infographics::plate_info(egui_ctx, gen_info),
+        MenuState::Hotspots=> infographics::hotspot_info(egui_ctx, gen_info),
         MenuState::AxialTilt => infographics::axial_tilt_info(egui_ctx, gen_info),
         MenuState::LapseRate => infographics::lapse_rate_info(egui_ctx),
         MenuState::BaseTemp => infographics::base_temp_info(egui_ctx, gen_info)
     }
  
-    if new_planet{
-        //creates new camera
-        let dimensions = display.get_framebuffer_dimensions();
-        let cam = graphics::Camera::new(dimensions.0 as f32/dimensions.1 as f32, 
-            glm::vec3(0.0,0.0,5.0), 
-            glm::Vec3::zeros(),
-            glm::Vec3::y());
-
-        //creates new planet with set perameters
-        let planet = planet::Planet::new(&display, &gen_info);
-        *game_state= GameState::Playing(planet, cam);
-    }
+    //use loaded planet, or create new planet with set perameters
+    let planet = match (loaded_planet,new_planet){
+        (Some(planet),_)=> planet,
+        (None,true)=> planet::Planet::new(gen_info),
+        (None,false)=> return,
+    };
+
+    //creates new camera
+    let dimensions = display.get_framebuffer_dimensions();
+    let cam = graphics::Camera::new(dimensions.0 as f32/dimensions.1 as f32, 
+        glm::vec3(0.0,0.0,5.0), 
+        glm::Vec3::zeros(),
+        glm::Vec3::y());
+
+    let render_data = RenderData::new(display, &Snapshot::new(&planet, false));
+    *game_state= GameState::Playing(SimWorker::spawn(planet), render_data, cam);
 }
 
 //menus for during the simulation
-pub fn playing(egui_ctx: &Context,params: &mut DrawParameters,planet:&mut planet::Planet){
+//the planet lives on the sim thread, so settings shown are from its latest snapshot and changes are sent back as edits
+pub fn playing(egui_ctx: &Context,params: &mut DrawParameters,worker:&mut SimWorker,render_data:&mut RenderData){
+    let snapshot = worker.latest();
+    let paused = snapshot.paused;
+    let mut sim_info = snapshot.sim_info.clone();
+    let mut scheduler = snapshot.scheduler.clone();
+    let mut plate_seed = snapshot.plate_seed;
+    //messages from the sim thread, such as if a save worked
+    let status = worker.message();
+
     //left side panel for controls
     egui::SidePanel::left("Left Panel").resizable(false)
     .show(egui_ctx,|ui| {
+        //nothing sent to the sim does anything once it has crashed, so make it clear why
+        if let Some(error) = worker.error(){
+            ui.colored_label(egui::Color32::RED, format!("Simulation stopped: {}", error));
+        }
+        ui.horizontal(|ui| {
+            if paused{
+                if ui.button("Resume").clicked(){
+                    worker.send(Command::Resume);
+                }
+            }else if ui.button("Pause").clicked(){
+                worker.send(Command::Pause);
+            }
+            if ui.button("Step").clicked(){
+                worker.send(Command::Step);
+            }
+        });
+
         ui.label("Years Per Second");
-        ui.add(egui::Slider::new(&mut planet.sim_info.years_per_second, 0.0..=1000000.0).logarithmic(true));
+        let changed = ui.add(egui::Slider::new(&mut sim_info.years_per_second, 0.0..=1000000.0).logarithmic(true)).changed();
+        send_setting(worker, changed, sim_info.years_per_second, |planet,value| planet.sim_info.years_per_second = value);
+        //warn if any system is dropping time to keep the frame rate up
+        let behind = scheduler.behind();
+        if !behind.is_empty(){
+            ui.colored_label(egui::Color32::YELLOW, format!("Falling behind: {}", behind.join(", ")));
+        }
+
+        ui.label("Tectonics Step (Years)");
+        let changed = ui.add(egui::Slider::new(&mut scheduler.tectonics.step, 10.0..=100000.0).logarithmic(true)).changed();
+        send_setting(worker, changed, scheduler.tectonics.step, |planet,value| planet.scheduler.tectonics.step = value);
+        let changed = ui.add(egui::Slider::new(&mut scheduler.tectonics.max_steps, 1..=256).text("Max Per Frame")).changed();
+        send_setting(worker, changed, scheduler.tectonics.max_steps, |planet,value| planet.scheduler.tectonics.max_steps = value);
+
+        ui.label("Temperature Step (Years)");
+        let changed = ui.add(egui::Slider::new(&mut scheduler.temperature.step, 0.01..=100.0).logarithmic(true)).changed();
+        send_setting(worker, changed, scheduler.temperature.step, |planet,value| planet.scheduler.temperature.step = value);
+        let changed = ui.add(egui::Slider::new(&mut scheduler.temperature.max_steps, 1..=256).text("Max Per Frame")).changed();
+        send_setting(worker, changed, scheduler.temperature.max_steps, |planet,value| planet.scheduler.temperature.max_steps = value);
+
+        ui.label("Isostasy Step (Years)");
+        let changed = ui.add(egui::Slider::new(&mut scheduler.isostasy.step, 10.0..=100000.0).logarithmic(true)).changed();
+        send_setting(worker, changed, scheduler.isostasy.step, |planet,value| planet.scheduler.isostasy.step = value);
+        let changed = ui.add(egui::Slider::new(&mut scheduler.isostasy.max_steps, 1..=256).text("Max Per Frame")).changed();
+        send_setting(worker, changed, scheduler.isostasy.max_steps, |planet,value| planet.scheduler.isostasy.max_steps = value);
+
+        ui.label("Erosion Step (Years)");
+        let changed = ui.add(egui::Slider::new(&mut scheduler.erosion.step, 10.0..=100000.0).logarithmic(true)).changed();
+        send_setting(worker, changed, scheduler.erosion.step, |planet,value| planet.scheduler.erosion.step = value);
+        let changed = ui.add(egui::Slider::new(&mut scheduler.erosion.max_steps, 1..=256).text("Max Per Frame")).changed();
+        send_setting(worker, changed, scheduler.erosion.max_steps, |planet,value| planet.scheduler.erosion.max_steps = value);
+
+        ui.label("Erodibility");
+        let changed = ui.add(egui::Slider::new(&mut sim_info.erodibility, 0.0..=0.0001).logarithmic(true)).changed();
+        send_setting(worker, changed, sim_info.erodibility, |planet,value| planet.sim_info.erodibility = value);
+
+        ui.label("Sediment Capacity");
+        let changed = ui.add(egui::Slider::new(&mut sim_info.sediment_capacity, 0.0..=10.0)).changed();
+        send_setting(worker, changed, sim_info.sediment_capacity, |planet,value| planet.sim_info.sediment_capacity = value);
+
+        ui.label("Hillslope Diffusivity (m^2/Year)");
+        let changed = ui.add(egui::Slider::new(&mut sim_info.diffusivity, 0.0..=10000000.0).logarithmic(true)).changed();
+        send_setting(worker, changed, sim_info.diffusivity, |planet,value| planet.sim_info.diffusivity = value);
+
+        ui.label("Talus Angle (Degrees)");
+        let changed = ui.add(egui::Slider::new(&mut sim_info.talus_angle, 0.1..=45.0).logarithmic(true)).changed();
+        send_setting(worker, changed, sim_info.talus_angle, |planet,value| planet.sim_info.talus_angle = value);
 
         ui.label("Terrain Scaling");
-        ui.add(egui::Slider::new(&mut planet.render_data.scale, 0.0..=0.05));
+        ui.add(egui::Slider::new(&mut render_data.scale, 0.0..=0.05));
 
         ui.label("Lapse Rate");
-        ui.add(egui::Slider::new(&mut planet.sim_info.lapse_rate, 0.0..=25.0));
+        let changed = ui.add(egui::Slider::new(&mut sim_info.lapse_rate, 0.0..=25.0)).changed();
+        send_setting(worker, changed, sim_info.lapse_rate, |planet,value| planet.sim_info.lapse_rate = value);
 
         ui.label("Base Temperature");
-        ui.add(egui::Slider::new(&mut planet.sim_info.base_temp, -100.0..=100.0));
-
+        let changed = ui.add(egui::Slider::new(&mut sim_info.base_temp, -100.0..=100.0)).changed();
+        send_setting(worker, changed, sim_info.base_temp, |planet,value| planet.sim_info.base_temp = value);
         ui.label("Light Source");
         egui::ComboBox::from_id_source("lighting")
-            .selected_text(format!("{:?}", planet.render_data.light_pos))
+            .selected_text(format!("{:?}", render_data.light_pos))
             .show_ui(ui, |ui| {
-                ui.selectable_value(&mut planet.render_data.light_pos, planet::LightPosition::Sun, "Sun");
-                ui.selectable_value(&mut planet.render_data.light_pos, planet::LightPosition::Camera, "Camera");
-                ui.selectable_value(&mut planet.render_data.light_pos, planet::LightPosition::Fixed, "Fixed");
+                ui.selectable_value(&mut render_data.light_pos, render::LightPosition::Sun, "Sun");
+                ui.selectable_value(&mut render_data.light_pos, render::LightPosition::Camera, "Camera");
+                ui.selectable_value(&mut render_data.light_pos, render::LightPosition::Fixed, "Fixed");
             }
         );
 
@@ -139,20 +287,124 @@ pub fn playing(egui_ctx: &Context,params: &mut DrawParameters,planet:&mut planet
 
         ui.label("Map Mode");
         egui::ComboBox::from_id_source("map_mode")
-            .selected_text(format!("{:?}", planet.render_data.map_mode))
+            .selected_text(format!("{:?}", render_data.map_mode))
             .show_ui(ui, |ui| {
-                ui.selectable_value(&mut planet.render_data.map_mode, planet::MapMode::Natural, "Natural");
-                ui.selectable_value(&mut planet.render_data.map_mode, planet::MapMode::Height, "Height");
-                ui.selectable_value(&mut planet.render_data.map_mode, planet::MapMode::Temperature, "Temperature");
-                ui.selectable_value(&mut planet.render_data.map_mode, planet::MapMode::Humidity, "Humidity");
-                ui.selectable_value(&mut planet.render_data.map_mode, planet::MapMode::Water, "Water");
-                ui.selectable_value(&mut planet.render_data.map_mode, planet::MapMode::Relief, "Relief");
-                ui.selectable_value(&mut planet.render_data.map_mode, planet::MapMode::Normals, "Normals");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::Natural, "Natural");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::Height, "Height");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::Temperature, "Temperature");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::Humidity, "Humidity");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::Water, "Water");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::Relief, "Relief");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::Normals, "Normals");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::Crust, "Crust");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::CrustThickness, "Crust Thickness");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::CrustDensity, "Crust Density");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::CrustAge, "Crust Age");
+                ui.selectable_value(&mut render_data.map_mode, render::MapMode::SeismicHazard, "Seismic Hazard");
             }
         );
+        ui.checkbox(&mut render_data.hotspot_overlay, "Hotspot Overlay");
+        ui.checkbox(&mut render_data.boundary_overlay, "Boundary Overlay");
+        ui.checkbox(&mut render_data.eruption_overlay, "Eruption Overlay");
 
+        ui.label("Plate Seed");
+        let changed = ui.add(egui::DragValue::new(&mut plate_seed).speed(0)).changed();
+        send_setting(worker, changed, plate_seed, |planet,value| planet.surface.plate_seed = value);
         if ui.button("New Plates").clicked(){
-            planet.surface.fill_plates();
+            worker.edit(move |planet| {
+                planet.surface.reseed_plates(plate_seed);
+                None
+            });
+        }
+        egui::CollapsingHeader::new("Plate Motion").show(ui, |ui| {
+            for (i,plate) in snapshot.plates.iter().enumerate(){
+                ui.label(format!("Plate {}: {} cells, {:.1} cm/yr", i, plate.cells, plate.velocity));
+                ui.label(format!("    Pole {:.0}, {:.0}", plate.pole.0, plate.pole.1));
+            }
+        });
+        egui::CollapsingHeader::new("Earthquakes").show(ui, |ui| {
+            for quake in snapshot.events.iter().filter(|e| e.kind == EventKind::Earthquake).take(EVENTS_SHOWN){
+                ui.label(format!("M{:.1} at {:.0}, {:.0}, {:.2} Myr", quake.magnitude, quake.location.0, quake.location.1, quake.time/1_000_000.0));
+            }
+        });
+        egui::CollapsingHeader::new("Eruptions").show(ui, |ui| {
+            let cooling = planet::systems::volcanic_cooling(snapshot.sim_info.aerosols);
+            ui.label(format!("Volcanic cooling: {:.2} C", cooling));
+            let eruptions = snapshot.events.iter()
+                .filter_map(|e| match e.kind{
+                    EventKind::Eruption(kind)=> Some((e,kind)),
+                    _=> None,
+                })
+                .take(EVENTS_SHOWN);
+            for (eruption,kind) in eruptions{
+                ui.label(format!("VEI {:.1} {:?} at {:.0}, {:.0}, {:.2} Myr", eruption.magnitude, kind, eruption.location.0, eruption.location.1, eruption.time/1_000_000.0));
+            }
+        });
+
+        ui.label("Save File");
+        let path = path_edit(ui, "save path", "planet.ups");
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked(){
+                let path = path.clone();
+                worker.edit(move |planet| Some(match planet.save(&path){
+                    Ok(())=> format!("Saved to {}", path),
+                    Err(e)=> format!("Failed to save: {}", e),
+                }));
+            }
+            if ui.button("Load").clicked(){
+                let path = path.clone();
+                worker.edit(move |planet| Some(match planet::Planet::load(&path){
+                    Ok(loaded)=> {
+                        *planet = loaded;
+                        format!("Loaded {}", path)
+                    },
+                    Err(e)=> format!("Failed to load: {}", e),
+                }));
+            }
+        });
+
+        ui.label("Export Maps To");
+        let export_dir = path_edit(ui, "export path", ".");
+        let mut map_width = ui_state(ui, "map width", 1024_usize);
+        ui.add(egui::Slider::new(&mut map_width, 64..=8192).logarithmic(true).text("Width"));
+        set_ui_state(ui, "map width", map_width);
+        let mut map_format = ui_state(ui, "map format", RasterFormat::Png);
+        egui::ComboBox::from_id_source("map_format")
+            .selected_text(format!("{:?}", map_format))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut map_format, RasterFormat::Png, "Png");
+                ui.selectable_value(&mut map_format, RasterFormat::Pgm, "Pgm");
+                ui.selectable_value(&mut map_format, RasterFormat::Raw, "Raw");
+            }
+        );
+        set_ui_state(ui, "map format", map_format);
+        if ui.button("Export Maps").clicked(){
+            //equirectangular so twice as wide as it is tall
+            worker.edit(move |planet| Some(match planet.export_rasters(Path::new(&export_dir), map_width, map_width/2, map_format){
+                Ok(paths)=> format!("Exported {} maps", paths.len()),
+                Err(e)=> format!("Failed to export: {}", e),
+            }));
         }
+
+        ui.label("Export Mesh To");
+        let mesh_path = path_edit(ui, "mesh path", "planet");
+        let mut mesh_format = ui_state(ui, "mesh format", MeshFormat::Obj);
+        egui::ComboBox::from_id_source("mesh_format")
+            .selected_text(format!("{:?}", mesh_format))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut mesh_format, MeshFormat::Obj, "Obj");
+                ui.selectable_value(&mut mesh_format, MeshFormat::Ply, "Ply");
+            }
+        );
+        set_ui_state(ui, "mesh format", mesh_format);
+        if ui.button("Export Mesh").clicked(){
+            //exaggerated the same as it is displayed
+            let scale = render_data.scale;
+            worker.edit(move |planet| Some(match planet.export_mesh(Path::new(&mesh_path), mesh_format, scale){
+                Ok(path)=> format!("Exported {}", path.display()),
+                Err(e)=> format!("Failed to export: {}", e),
+            }));
+        }
+        file_status(ui, status);
     });
-}
\ No newline at end of file
+}
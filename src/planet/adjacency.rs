@@ -0,0 +1,50 @@
+//which cells are connected to which, read straight off the surface mesh so there is only one connectivity structure to keep up to date
+//the mesh keeps every cells neighbours sorted as it changes, so systems can ask for them as often as they like
+
+//internal modules
+use crate::graphics::halfedge::HalfEdgeMesh;
+use super::utils::lowest_first;
+
+//connections of the cells in a mesh
+//everything is given sorted, so the order only depends on the mesh and not the order it was changed in
+//otherwise a reloaded planet would pick different cells than the one it was saved from
+#[derive(Clone, Copy)]
+pub struct Adjacency<'a>{
+    mesh: &'a HalfEdgeMesh,
+}
+impl<'a> Adjacency<'a>{
+    pub fn new(mesh:&'a HalfEdgeMesh)->Adjacency<'a>{
+        Adjacency { mesh }
+    }
+
+    //cells directly connected to a cell, lowest first
+    pub fn neighbours(&self, cell:usize)->impl Iterator<Item = usize> + 'a{
+        self.mesh.neighbours(cell as u32).iter().map(|n| *n as usize)
+    }
+
+    //how many cells a cell is connected to
+    pub fn degree(&self, cell:usize)->usize{
+        self.mesh.neighbours(cell as u32).len()
+    }
+
+    //triangles a cell is part of, each starting from its lowest index
+    pub fn triangles(&self, cell:usize)->Vec<[u32;3]>{
+        let mut triangles:Vec<[u32;3]> = self.mesh.vertex_faces(cell as u32).map(lowest_first).collect();
+        triangles.sort();
+        triangles
+    }
+
+    //whether two cells are connected
+    pub fn is_edge(&self, a:usize, b:usize)->bool{
+        self.mesh.is_edge(a as u32, b as u32)
+    }
+
+    //every edge in the mesh once, with the lower index first
+    pub fn edges(&self)->impl Iterator<Item = (usize,usize)> + 'a{
+        let adjacency = *self;
+        (0..self.mesh.vertex_count())
+            .flat_map(move |a| adjacency.neighbours(a)
+                .filter(move |b| a<*b)
+                .map(move |b| (a,b)))
+    }
+}
@@ -0,0 +1,71 @@
+//plate boundaries, classified edge by edge from how the plates either side are moving relative to each other
+//kept up to date by the sim, so anything wanting to know where plates meet doesnt have to work it out from the mesh
+
+//boundaries where plates slide along each other faster than this many times the speed they move together or apart are transform
+const TRANSFORM_RATIO: f32 = 1.0;
+
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum BoundaryType{
+    //plates moving together, one sinking under the other or both crumpling up
+    Convergent,
+    //plates moving apart, with new crust forming between them
+    Divergent,
+    //plates sliding past each other
+    Transform,
+}
+
+//an edge of the mesh between cells on different plates
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub struct BoundaryEdge{
+    //cells either side, lower index first
+    pub cells: (usize,usize),
+    pub kind: BoundaryType,
+    //speed the plates move together across the boundary, negative if moving apart, in radians per year
+    pub closing: f32,
+    //speed the plates slide along the boundary, in radians per year
+    pub shear: f32,
+}
+impl BoundaryEdge{
+    pub fn new(cells:(usize,usize), closing:f32, shear:f32)->BoundaryEdge{
+        //plates not moving across the boundary at all, e.g. both at rest, neither push nor pull so count as transform
+        let kind = if closing == 0.0 || shear > closing.abs()*TRANSFORM_RATIO{
+            BoundaryType::Transform
+        }else if closing > 0.0{
+            BoundaryType::Convergent
+        }else{
+            BoundaryType::Divergent
+        };
+        BoundaryEdge { cells, kind, closing, shear }
+    }
+}
+
+//which kinds of boundary each cell is on, sent to the gpu for the boundary overlay
+//each is 1 if the cell has an edge of that kind and 0 if not, so they blend smoothly across triangles
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+#[derive(Default)]
+pub struct BoundaryData{
+    pub convergent: f32,
+    pub divergent: f32,
+    pub transform: f32,
+}
+glium::implement_vertex!(BoundaryData,convergent,divergent,transform);
+
+//boundary data for every cell from a list of boundary edges
+pub fn boundary_data(cell_count:usize, edges:&[BoundaryEdge])->Vec<BoundaryData>{
+    let mut data = vec![BoundaryData::default();cell_count];
+    for edge in edges{
+        for cell in [edge.cells.0,edge.cells.1]{
+            let flag = match edge.kind{
+                BoundaryType::Convergent=> &mut data[cell].convergent,
+                BoundaryType::Divergent=> &mut data[cell].divergent,
+                BoundaryType::Transform=> &mut data[cell].transform,
+            };
+            *flag = 1.0;
+        }
+    }
+    data
+}
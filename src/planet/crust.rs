@@ -0,0 +1,182 @@
+//geological state of the crust under each cell, kept apart from the cell data as most of it is only needed by the sim
+//continental crust is thick and light so stays at the surface, oceanic crust is born thin at ridges and gets denser as it cools until it sinks
+
+//height new oceanic crust is born at along a mid ocean ridge, in km
+pub const RIDGE_HEIGHT: f32 = -2.5;
+//km the sea floor sinks by for every square root of a million years since it formed, as it cools and contracts
+pub const SUBSIDENCE: f32 = 0.35;
+//age in years after which the sea floor has cooled fully and stops sinking
+pub const SUBSIDENCE_AGE: f32 = 80_000_000.0;
+
+//anything generated above this height, in km, is taken to be continent, so shelf seas are still continental
+const SHELF_DEPTH: f32 = -1.0;
+//typical thickness of oceanic crust, in km
+const OCEANIC_THICKNESS: f32 = 7.0;
+//densities of each type of crust, in g/cm^3, oceanic crust getting denser by AGED_DENSITY as it cools fully
+const CONTINENTAL_DENSITY: f32 = 2.7;
+const OCEANIC_DENSITY: f32 = 2.9;
+const AGED_DENSITY: f32 = 0.1;
+//age given to continents at generation, in years
+const CRATON_AGE: f32 = 2_000_000_000.0;
+//years for the swell left by a mantle plume to mostly cool away
+const SWELL_COOLING: f32 = 20_000_000.0;
+//years for the memory of an earthquake to mostly fade from the hazard map
+const HAZARD_MEMORY: f32 = 100_000.0;
+//years for the glow of a fresh eruption to mostly fade
+const ERUPTION_MEMORY: f32 = 20_000.0;
+//density of the mantle crust floats on, in g/cm^3
+const MANTLE_DENSITY: f32 = 3.3;
+//km crust would stand above the mantle if it were all above sea level, taken away from the height crust floats at to give its height from sea level
+//set so new crust at a ridge floats at ridge height
+const COMPENSATION: f32 = 6.5;
+
+//height of sea floor of a given age in years, following the square root of age cooling curve
+pub fn ocean_depth(age:f32)->f32{
+    RIDGE_HEIGHT-SUBSIDENCE*(age.clamp(0.0, SUBSIDENCE_AGE)/1_000_000.0).sqrt()
+}
+
+//age in years sea floor at a given height would be if it had only subsided since forming, zero for anything above the ridge
+pub fn ocean_age(height:f32)->f32{
+    let sunk = f32::max(RIDGE_HEIGHT-height, 0.0)/SUBSIDENCE;
+    f32::min(sunk*sunk*1_000_000.0, SUBSIDENCE_AGE)
+}
+
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum CrustType{
+    Continental,
+    Oceanic,
+}
+
+#[derive(Clone, Copy)]
+#[derive(PartialEq)]
+#[derive(Debug)]
+pub struct Crust{
+    pub kind: CrustType,
+    //in km
+    pub thickness: f32,
+    //in g/cm^3, denser crust sinks beneath lighter crust
+    pub density: f32,
+    //years since the crust formed
+    pub age: f32,
+    //plate the crust formed on, which stays the same even if the cell changes plate
+    pub origin: Option<usize>,
+    //km the crust is held up by heat from a mantle plume under it, lost as it drifts away and cools
+    pub swell: f32,
+    //m of slip locked up in faults through the crust, built up at plate boundaries and released by earthquakes
+    pub strain: f32,
+    //m of strain the faults through the crust break at, rolled again after every break, 0 until first loaded
+    pub rupture: f32,
+    //magnitude of the strongest recent earthquake to shake the crust, fading over time
+    pub hazard: f32,
+    //explosivity of the biggest recent eruption through the crust, fading over time
+    pub eruption: f32,
+}
+impl Crust{
+    pub fn oceanic(age:f32,origin:Option<usize>)->Crust{
+        Crust { kind: CrustType::Oceanic, thickness: OCEANIC_THICKNESS, density: oceanic_density(age), age, origin, swell: 0.0, strain: 0.0, rupture: 0.0, hazard: 0.0, eruption: 0.0 }
+    }
+
+    //as thick as it needs to be to float at height, so higher ground has deeper roots
+    pub fn continental(height:f32,age:f32,origin:Option<usize>)->Crust{
+        let mut crust = Crust { kind: CrustType::Continental, thickness: 0.0, density: CONTINENTAL_DENSITY, age, origin, swell: 0.0, strain: 0.0, rupture: 0.0, hazard: 0.0, eruption: 0.0 };
+        crust.thickness = (height+COMPENSATION)/crust.buoyancy();
+        crust
+    }
+
+    //crust suggested by the height of the surface above it, for when nothing else is known
+    //sea floor is given the age its depth suggests, so it carries on subsiding from where it is
+    pub fn from_height(height:f32,origin:Option<usize>)->Crust{
+        if height >= SHELF_DEPTH{
+            Crust::continental(height, CRATON_AGE, origin)
+        }else{
+            Crust::oceanic(ocean_age(height), origin)
+        }
+    }
+
+    pub fn is_continental(&self)->bool{
+        self.kind == CrustType::Continental
+    }
+
+    //km of surface held up for every km of crust, as most of it sits below the surface in the mantle
+    fn buoyancy(&self)->f32{
+        1.0-self.density/MANTLE_DENSITY
+    }
+
+    //height the surface above this crust would settle at if left to float in the mantle
+    //young sea floor and anything over a plume is also held up by the hot mantle under it, which is lost as it cools
+    pub fn equilibrium_height(&self)->f32{
+        let thermal = match self.kind{
+            CrustType::Continental=> 0.0,
+            CrustType::Oceanic=> ocean_depth(self.age)-ocean_depth(SUBSIDENCE_AGE),
+        };
+        self.thickness*self.buoyancy()-COMPENSATION+thermal+self.swell
+    }
+
+    //thickens the crust enough to hold up the surface by an extra amount of km, or thins it for a negative amount
+    pub fn support(&mut self,height:f32){
+        self.thickness = (self.thickness+height/self.buoyancy()).max(0.0);
+    }
+
+    //ages crust, returning how many km the surface above it sinks as it cools
+    pub fn age_by(&mut self,years:f32)->f32{
+        let before = self.age;
+        self.age += years;
+        self.hazard *= (-years/HAZARD_MEMORY).exp();
+        self.eruption *= (-years/ERUPTION_MEMORY).exp();
+        let cooled = self.swell*(1.0-(-years/SWELL_COOLING).exp());
+        self.swell -= cooled;
+        match self.kind{
+            CrustType::Continental=> cooled,
+            CrustType::Oceanic=> {
+                self.density = oceanic_density(self.age);
+                ocean_depth(before)-ocean_depth(self.age)+cooled
+            },
+        }
+    }
+
+    //folds in continental crust from a cell that has left the mesh, share being the fraction of it this cell takes
+    //continental crust is too light to be lost, so anything it folds into gets thicker and becomes continental too
+    pub fn fold(&mut self,other:&Crust,share:f32){
+        if !other.is_continental(){
+            return;
+        }
+        self.kind = CrustType::Continental;
+        self.density = CONTINENTAL_DENSITY;
+        self.thickness += other.thickness*share;
+    }
+
+    //what gets sent to the gpu for drawing
+    pub fn data(&self)->CrustData{
+        CrustData{
+            continental: if self.is_continental() {1.0} else {0.0},
+            thickness: self.thickness,
+            crust_density: self.density,
+            //in millions of years, as years are too big for the shaders to do much with
+            crust_age: self.age/1_000_000.0,
+            swell: self.swell,
+            hazard: self.hazard,
+            eruption: self.eruption,
+        }
+    }
+}
+
+fn oceanic_density(age:f32)->f32{
+    OCEANIC_DENSITY+AGED_DENSITY*(age/SUBSIDENCE_AGE).clamp(0.0, 1.0)
+}
+
+//crust of each cell as sent to the gpu, alongside the cell data
+#[derive(Copy, Clone)]
+#[derive(PartialEq)]
+pub struct CrustData{
+    //1 if continental, 0 if oceanic
+    pub continental: f32,
+    pub thickness: f32,
+    pub crust_density: f32,
+    pub crust_age: f32,
+    pub swell: f32,
+    pub hazard: f32,
+    pub eruption: f32,
+}
+glium::implement_vertex!(CrustData,continental,thickness,crust_density,crust_age,swell,hazard,eruption);
@@ -0,0 +1,79 @@
+//things that happen to the planet at a moment in time, such as earthquakes and eruptions
+//kept in a log so they can be looked back on and shown where they happened, only the newest of each kind are kept
+use std::collections::VecDeque;
+
+//external crates
+use nalgebra_glm as glm;
+
+//most events of each kind kept, older ones are dropped as new ones come in
+//kept per kind so common events like earthquakes dont push out rarer ones
+pub const LOG_SIZE: usize = 256;
+
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum EventKind{
+    Earthquake,
+    Eruption(VolcanoKind),
+}
+
+//what feeds a volcano
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum VolcanoKind{
+    //melt rising off a sinking plate
+    Arc,
+    //mantle welling up where plates pull apart
+    Rift,
+    //a mantle plume
+    Hotspot,
+}
+
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub struct Event{
+    pub kind: EventKind,
+    //where it happened on the unit sphere, which stays put as the cells there move on
+    pub position: glm::Vec3,
+    //moment magnitude for earthquakes, volcanic explosivity index for eruptions
+    pub magnitude: f32,
+    //years since the planet formed
+    pub time: f64,
+}
+
+#[derive(Clone)]
+#[derive(Default)]
+pub struct EventLog{
+    //oldest first
+    earthquakes: VecDeque<Event>,
+    eruptions: VecDeque<Event>,
+}
+impl EventLog{
+    pub fn push(&mut self, event:Event){
+        let events = match event.kind{
+            EventKind::Earthquake=> &mut self.earthquakes,
+            EventKind::Eruption(_)=> &mut self.eruptions,
+        };
+        if events.len() == LOG_SIZE{
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    //every event kept, oldest first
+    pub fn iter(&self)->impl DoubleEndedIterator<Item = &Event> + '_{
+        let mut events:Vec<&Event> = self.earthquakes.iter().chain(self.eruptions.iter()).collect();
+        //stable, so events from the same step stay in the order the systems ran in
+        events.sort_by(|a,b| a.time.total_cmp(&b.time));
+        events.into_iter()
+    }
+
+    pub fn len(&self)->usize{
+        self.earthquakes.len()+self.eruptions.len()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.earthquakes.is_empty() && self.eruptions.is_empty()
+    }
+}
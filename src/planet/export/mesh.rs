@@ -0,0 +1,117 @@
+//exports the surface as a mesh, for use in blender and the like
+use std::{collections::HashMap, fs::File, io::{self, BufWriter, Write}, path::{Path, PathBuf}};
+
+//external crates
+use nalgebra_glm as glm;
+
+//internal modules
+use crate::planet::{Planet, surface::Surface};
+use super::CellField;
+
+//formats the mesh can be written in
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum MeshFormat{
+    //wavefront obj, geometry only
+    Obj,
+    //binary ply, with every cell field and plate id as vertex properties
+    Ply,
+}
+impl MeshFormat{
+    pub fn extension(&self)->&'static str{
+        match self{
+            MeshFormat::Obj=> "obj",
+            MeshFormat::Ply=> "ply",
+        }
+    }
+}
+
+//the live part of the surface, with banked cells left out and indices made contiguous
+pub struct SurfaceMesh{
+    //displaced position of each vertex
+    pub positions: Vec<glm::Vec3>,
+    //cell each vertex came from
+    pub cells: Vec<usize>,
+    //triangles indexing into positions, wound the same as the surface
+    pub triangles: Vec<u32>,
+}
+impl SurfaceMesh{
+    //scale exaggerates height the same way as the renderer, only land is raised
+    pub fn new(surface:&Surface, scale:f32)->SurfaceMesh{
+        //cell index to new vertex index
+        let mut remap:HashMap<u32,u32> = HashMap::new();
+        let mut cells:Vec<usize> = Vec::new();
+        let triangles = surface.mesh.indices().iter()
+            .map(|i| *remap.entry(*i).or_insert_with(|| {
+                cells.push(*i as usize);
+                (cells.len()-1) as u32
+            }))
+            .collect();
+        let positions = cells.iter()
+            .map(|c| {
+                let cell = &surface.cells[*c];
+                cell.position*(1.0+f32::max(cell.contents.height,0.0)*scale)
+            })
+            .collect();
+        SurfaceMesh { positions, cells, triangles }
+    }
+
+    pub fn write_obj(&self, w:&mut impl Write)->io::Result<()>{
+        writeln!(w, "# untitled planet sim surface, {} vertices, {} faces", self.positions.len(), self.triangles.len()/3)?;
+        for p in self.positions.iter(){
+            writeln!(w, "v {} {} {}", p.x, p.y, p.z)?;
+        }
+        //obj indices start at 1
+        for tri in self.triangles.chunks(3){
+            writeln!(w, "f {} {} {}", tri[0]+1, tri[1]+1, tri[2]+1)?;
+        }
+        Ok(())
+    }
+
+    //cell fields and plate are written as extra vertex properties, plate is -1 if the cell has none
+    pub fn write_ply(&self, surface:&Surface, w:&mut impl Write)->io::Result<()>{
+        writeln!(w, "ply")?;
+        writeln!(w, "format binary_little_endian 1.0")?;
+        writeln!(w, "comment untitled planet sim surface")?;
+        writeln!(w, "element vertex {}", self.positions.len())?;
+        for axis in ["x","y","z"]{
+            writeln!(w, "property float {}", axis)?;
+        }
+        for field in CellField::ALL.iter(){
+            writeln!(w, "property float {}", field.name())?;
+        }
+        writeln!(w, "property int plate_id")?;
+        writeln!(w, "element face {}", self.triangles.len()/3)?;
+        writeln!(w, "property list uchar uint vertex_indices")?;
+        writeln!(w, "end_header")?;
+
+        for (p,c) in self.positions.iter().zip(self.cells.iter()){
+            let cell = &surface.cells[*c];
+            p.iter().try_for_each(|x| w.write_all(&x.to_le_bytes()))?;
+            CellField::ALL.iter().try_for_each(|field| w.write_all(&field.value(&cell.contents).to_le_bytes()))?;
+            w.write_all(&cell.plate.map_or(-1, |p| p as i32).to_le_bytes())?;
+        }
+        for tri in self.triangles.chunks(3){
+            w.write_all(&[3])?;
+            tri.iter().try_for_each(|i| w.write_all(&i.to_le_bytes()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Planet{
+    //writes the surface as a mesh at path, with the formats extension added
+    //returns path of the file written
+    pub fn export_mesh(&self, path:&Path, format:MeshFormat, scale:f32)->io::Result<PathBuf>{
+        let path = path.with_extension(format.extension());
+        let mesh = SurfaceMesh::new(&self.surface, scale);
+        let mut w = BufWriter::new(File::create(&path)?);
+        match format{
+            MeshFormat::Obj=> mesh.write_obj(&mut w)?,
+            MeshFormat::Ply=> mesh.write_ply(&self.surface, &mut w)?,
+        }
+        w.flush()?;
+        Ok(path)
+    }
+}
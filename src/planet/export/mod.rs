@@ -0,0 +1,55 @@
+//getting data out of the sim for use in other tools
+//all exports work on the planet alone, so can be used headless aswell as from the ui
+
+//internal modules
+use super::surface::CellData;
+
+//child modules
+pub mod raster;
+pub mod mesh;
+
+//fields of a cell that can be exported
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum CellField{
+    Height,
+    Temperature,
+    Humidity,
+    Water,
+}
+impl CellField{
+    //every exportable field, used when exporting one file per field
+    pub const ALL: [CellField;4] = [CellField::Height, CellField::Temperature, CellField::Humidity, CellField::Water];
+
+    //gets this field out of a cells data
+    pub fn value(&self, data:&CellData)->f32{
+        match self{
+            CellField::Height=> data.height,
+            CellField::Temperature=> data.temperature,
+            CellField::Humidity=> data.humidity,
+            CellField::Water=> data.water,
+        }
+    }
+
+    //name used for files of this field
+    pub fn name(&self)->&'static str{
+        match self{
+            CellField::Height=> "height",
+            CellField::Temperature=> "temperature",
+            CellField::Humidity=> "humidity",
+            CellField::Water=> "water",
+        }
+    }
+
+    //range the field is expected to be in, same as used by the shaders
+    //used to fit values into integer formats so exports from different times can be compared
+    pub fn range(&self)->(f32,f32){
+        match self{
+            CellField::Height=> (-10.0,10.0),
+            CellField::Temperature=> (-50.0,50.0),
+            CellField::Humidity=> (0.0,100.0),
+            CellField::Water=> (0.0,1.0),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//resamples cell fields onto an equirectangular (lat long) grid and writes them as images or raw grids
+use std::{fs::File, io::{self, BufWriter, Write}, path::{Path, PathBuf}};
+
+//external crates
+use nalgebra_glm as glm;
+
+//internal modules
+use crate::planet::{Planet, surface::Surface, utils::MapFrame};
+use super::CellField;
+
+//formats a raster can be written in
+#[derive(PartialEq)]
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub enum RasterFormat{
+    //16 bit binary greyscale pgm, values scaled to the fields range
+    Pgm,
+    //16 bit greyscale png, values scaled to the fields range
+    Png,
+    //raw little endian f32 values, row by row, with no header
+    Raw,
+}
+impl RasterFormat{
+    pub fn extension(&self)->&'static str{
+        match self{
+            RasterFormat::Pgm=> "pgm",
+            RasterFormat::Png=> "png",
+            RasterFormat::Raw=> "f32",
+        }
+    }
+}
+
+//grid of values covering the planet, row 0 is the north pole, column 0 is at -180 degrees longitude
+pub struct Raster{
+    pub width: usize,
+    pub height: usize,
+    //values stored row by row
+    pub values: Vec<f32>,
+}
+impl Raster{
+    //writes raster, range is what gets mapped to 0 and the max value in integer formats
+    pub fn write(&self, w:&mut impl Write, format:RasterFormat, range:(f32,f32))->io::Result<()>{
+        match format{
+            RasterFormat::Pgm=> {
+                write!(w, "P5\n{} {}\n65535\n", self.width, self.height)?;
+                w.write_all(&self.to_u16_bytes(range))
+            },
+            RasterFormat::Png=> {
+                let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+                encoder.set_color(png::ColorType::Grayscale);
+                encoder.set_depth(png::BitDepth::Sixteen);
+                encoder.write_header()?.write_image_data(&self.to_u16_bytes(range))?;
+                Ok(())
+            },
+            RasterFormat::Raw=> {
+                self.values.iter().try_for_each(|v| w.write_all(&v.to_le_bytes()))
+            },
+        }
+    }
+
+    //values scaled into range as big endian u16s, as both pgm and png want
+    fn to_u16_bytes(&self, range:(f32,f32))->Vec<u8>{
+        self.values.iter()
+            .flat_map(|v| {
+                let normalized = ((v-range.0)/(range.1-range.0)).clamp(0.0, 1.0);
+                ((normalized*u16::MAX as f32).round() as u16).to_be_bytes()
+            })
+            .collect()
+    }
+}
+
+//for every pixel in a lat long grid, the cells and weights that its value is made from
+//worked out once so every field can then be sampled cheaply
+pub struct RasterSampler{
+    pub width: usize,
+    pub height: usize,
+    weights: Vec<[(usize,f32);3]>,
+}
+impl RasterSampler{
+    //finds which triangle of the surface every pixel lands in, and its barycentric coords in it
+    //north is the direction of the north pole of the map, usually the planets axis
+    pub fn new(surface:&Surface, north:&glm::Vec3, width:usize, height:usize)->RasterSampler{
+        let frame = MapFrame::new(north);
+        let mut weights:Vec<Option<[(usize,f32);3]>> = vec![None;width*height];
+
+        //converts lat long to fractional pixel coords, and back from whole pixels
+        let to_col = |lon:f32| (lon+std::f32::consts::PI)/std::f32::consts::TAU*width as f32-0.5;
+        let to_row = |lat:f32| (std::f32::consts::FRAC_PI_2-lat)/std::f32::consts::PI*height as f32-0.5;
+        let pixel_dir = |col:usize,row:usize| frame.direction(
+            std::f32::consts::FRAC_PI_2-(row as f32+0.5)/height as f32*std::f32::consts::PI,
+            (col as f32+0.5)/width as f32*std::f32::consts::TAU-std::f32::consts::PI);
+
+        for tri in surface.mesh.faces(){
+            let points:Vec<glm::Vec3> = tri.iter()
+                .map(|i| surface.cells[*i as usize].position.normalize())
+                .collect();
+            let coords:Vec<(f32,f32)> = points.iter().map(|p| frame.lat_long(p)).collect();
+
+            //rows covered, with a pixel of margin as edges are arcs that can bulge past their corners
+            let mut lat_max = coords.iter().map(|c| c.0).fold(f32::MIN, f32::max);
+            let mut lat_min = coords.iter().map(|c| c.0).fold(f32::MAX, f32::min);
+            //columns covered, a triangle around a pole covers all of them
+            let mut cols = 0..width as isize;
+            if barycentric(&frame.north, &points).is_some(){
+                lat_max = std::f32::consts::FRAC_PI_2;
+            }else if barycentric(&-frame.north, &points).is_some(){
+                lat_min = -std::f32::consts::FRAC_PI_2;
+            }else{
+                let mut lons:Vec<f32> = coords.iter().map(|c| c.1).collect();
+                //if spread over more than half the planet it actually crosses the seam, so shift it to be continuous
+                let spread = lons.iter().fold(f32::MIN, |a,b| a.max(*b)) - lons.iter().fold(f32::MAX, |a,b| a.min(*b));
+                if spread > std::f32::consts::PI{
+                    lons.iter_mut().filter(|l| **l < 0.0).for_each(|l| *l += std::f32::consts::TAU);
+                }
+                let lon_min = lons.iter().fold(f32::MAX, |a,b| a.min(*b));
+                let lon_max = lons.iter().fold(f32::MIN, |a,b| a.max(*b));
+                cols = (to_col(lon_min).floor() as isize-1)..(to_col(lon_max).ceil() as isize+2);
+                if cols.len() > width{
+                    cols = 0..width as isize;
+                }
+            }
+            let rows = (to_row(lat_max).floor() as isize-1).max(0)..(to_row(lat_min).ceil() as isize+2).min(height as isize);
+
+            for row in rows.map(|r| r as usize){
+                for col in cols.clone().map(|c| c.rem_euclid(width as isize) as usize){
+                    let pixel = &mut weights[row*width+col];
+                    if pixel.is_some(){
+                        continue;
+                    }
+                    if let Some(bary) = barycentric(&pixel_dir(col,row), &points){
+                        *pixel = Some([
+                            (tri[0] as usize,bary.x),
+                            (tri[1] as usize,bary.y),
+                            (tri[2] as usize,bary.z)]);
+                    }
+                }
+            }
+        }
+
+        //anything not covered, from holes in the mesh, just takes the value of the closest cell in use
+        //found by walking the mesh towards the pixel from the cell of the pixel before, which is never far
+        let mut last = surface.mesh.faces().next().map_or(0, |tri| tri[0] as usize);
+        let weights = weights.into_iter()
+            .enumerate()
+            .map(|(i,w)| {
+                let weights = w.unwrap_or_else(|| {
+                    let closest = closest_cell(surface, last, &pixel_dir(i%width,i/width));
+                    [(closest,1.0),(closest,0.0),(closest,0.0)]
+                });
+                last = weights.iter().max_by(|a,b| a.1.total_cmp(&b.1)).unwrap().0;
+                weights
+            })
+            .collect();
+
+        RasterSampler { width, height, weights }
+    }
+
+    //interpolates field across every pixel
+    pub fn sample(&self, surface:&Surface, field:CellField)->Raster{
+        Raster{
+            width: self.width,
+            height: self.height,
+            values: self.weights.iter()
+                .map(|w| w.iter()
+                    .map(|(cell,weight)| field.value(&surface.cells[*cell].contents)*weight)
+                    .sum())
+                .collect(),
+        }
+    }
+}
+
+//cell closest to a direction, walking from a starting cell to whichever neighbour is closer until none are
+//greedy walks like this always reach the closest cell on a delaunay mesh
+fn closest_cell(surface:&Surface, start:usize, dir:&glm::Vec3)->usize{
+    let closeness = |c:usize| glm::dot(&surface.cells[c].position.normalize(),dir);
+    let mut current = start;
+    while let Some(next) = surface.adjacency().neighbours(current)
+        .filter(|n| closeness(*n) > closeness(current))
+        .max_by(|a,b| closeness(*a).total_cmp(&closeness(*b))){
+        current = next;
+    }
+    current
+}
+
+//where a ray from the center of the planet hits a triangle, as barycentric coords, if it does at all
+fn barycentric(dir:&glm::Vec3, tri:&[glm::Vec3])->Option<glm::Vec3>{
+    //small tolerance so pixels exactly on shared edges arent missed
+    const EPSILON:f32 = 1e-5;
+    let ab = tri[1]-tri[0];
+    let ac = tri[2]-tri[0];
+    let p = glm::cross(dir,&ac);
+    let det = glm::dot(&ab,&p);
+    if det.abs() < f32::EPSILON{
+        return None;
+    }
+    //ray starts at the origin
+    let t_vec = -tri[0];
+    let u = glm::dot(&t_vec,&p)/det;
+    let q = glm::cross(&t_vec,&ab);
+    let v = glm::dot(dir,&q)/det;
+    let t = glm::dot(&ac,&q)/det;
+    if u < -EPSILON || v < -EPSILON || u+v > 1.0+EPSILON || t <= 0.0{
+        return None;
+    }
+    Some(glm::vec3(1.0-u-v,u,v))
+}
+
+impl Planet{
+    //writes every cell field as its own lat long raster in dir, north being the planets axis
+    //returns paths of the files written
+    pub fn export_rasters(&self, dir:&Path, width:usize, height:usize, format:RasterFormat)->io::Result<Vec<PathBuf>>{
+        let sampler = RasterSampler::new(&self.surface, &self.sim_info.axis, width, height);
+        CellField::ALL.iter()
+            .map(|field| {
+                let path = dir.join(format!("{}.{}", field.name(), format.extension()));
+                let mut w = BufWriter::new(File::create(&path)?);
+                sampler.sample(&self.surface, *field).write(&mut w, format, field.range())?;
+                w.flush()?;
+                Ok(path)
+            })
+            .collect()
+    }
+}
@@ -1,51 +1,45 @@
 //external crates
-use glium::{Surface,glutin};
 use nalgebra_glm as glm;
-use rand::rngs::ThreadRng;
 
 //other internal modules
 use crate::graphics;
 
-use self::surface::CellData;
-
 //child modules
 pub mod surface;
+pub mod crust;
+pub mod boundary;
+pub mod events;
+pub mod adjacency;
 pub mod utils;
 pub mod systems;
-
-#[derive(PartialEq)]
-#[derive(Debug)]
-pub enum LightPosition{
-    Sun,
-    Camera,
-    Fixed
-}
-
-#[derive(PartialEq)]
-#[derive(Clone, Copy)]
-#[derive(Debug)]
-pub enum MapMode{
-    Natural,
-    Height,
-    Temperature,
-    Humidity,
-    Water,
-    Relief,
-    Normals,
-}
+pub mod save;
+pub mod export;
+pub mod scheduler;
+pub mod worker;
+
+//in m, speeds are kept in radians per year so this turns them back into distances
+pub const PLANET_RADIUS: f32 = 6_371_000.0;
+//erosion rates new planets start with, enough to wear a mountain range down over tens of millions of years
+pub const DEFAULT_ERODIBILITY: f32 = 0.000_001;
+pub const DEFAULT_SEDIMENT_CAPACITY: f32 = 1.0;
+pub const DEFAULT_DIFFUSIVITY: f32 = 50_000.0;
+pub const DEFAULT_TALUS_ANGLE: f32 = 1.0;
 
 //info used for generating planet
 pub struct GenInfo{
-    pub menu_state: crate::menus::MenuState,
     pub iterations :u8,
     pub seed:u32,
     pub plate_no:u32,
+    pub hotspot_no:u32,
+    //km of volcanic rock a hotspot piles up every million years at its strongest, each hotspot varies around this
+    pub hotspot_strength:f32,
     pub axial_tilt:f32,
     pub lapse_rate: f32,
     pub base_temp:f32
 }
 
 //information for the general running of the simulation, not specific to cells or surface
+#[derive(Clone)]
 pub struct SimInfo{
     //years passing per second in the sim
     pub years_per_second: f32,
@@ -59,32 +53,29 @@ pub struct SimInfo{
     pub axis: glm::Vec3,
     //vector pointing to orbital center
     pub to_sun: glm::Vec3,
+    //optical depth of volcanic aerosols in the stratosphere, shading and cooling the whole planet
+    pub aerosols: f32,
+    //how easily rivers cut into rock, the k of the stream power law, in m per year for every m of square root drainage area at a slope of 1
+    pub erodibility: f32,
+    //sediment a river can carry, as a multiple of what it could erode where it is, anything more is dropped
+    pub sediment_capacity: f32,
+    //how fast soil creeps down hillslopes, in m^2 per year
+    pub diffusivity: f32,
+    //steepest slope ground can hold before it slides, in degrees, far gentler than real scree as each cell averages over hundreds of km
+    pub talus_angle: f32,
 }
 
-//struct containing all things needed passed to the gpu
-pub struct RenderData{
-    //buffer containing cell data needed for rendering
-    planet_data: glium::VertexBuffer<surface::CellData>,
-    //indices, define triangles of planet
-    indices: glium::IndexBuffer<u32>,
-    //how exagerated the planet surface will be
-    pub scale: f32,
-    //where the light source is
-    pub light_pos: LightPosition,
-    //map mode to use when displaying the planet
-    pub map_mode: MapMode,
-}
-
+//the simulation itself, needs no display so can be run headless
 pub struct Planet{
-    //data related to rendering the planet and for the shaders
-    pub render_data: RenderData,
     //data describing the planets surface
     pub surface: surface::Surface,
     //infromation used for the general running of the simulation
-    pub sim_info: SimInfo
+    pub sim_info: SimInfo,
+    //decides how much each system runs every frame
+    pub scheduler: scheduler::Scheduler,
 }
 impl Planet{
-    pub fn new(display:&glium::Display, gen:&GenInfo)->Planet{
+    pub fn new(gen:&GenInfo)->Planet{
         //tilts planet axis as specified around the z
         let axis = glm::rotate_z_vec3( &glm::vec3(0.0,1.0,0.0),gen.axial_tilt);
 
@@ -94,84 +85,81 @@ impl Planet{
             .normalize();
         
         //creates planet surface
-        let mut surface = surface::Surface::new(base_shape,gen);
-
-        //extract data for buffer
-        let surface_contents:Vec<CellData> = surface.cells.iter()
-            .map(|c|
-            c.contents)
-            .collect();
+        let surface = surface::Surface::new(base_shape,gen);
 
         Planet{
-            render_data: 
-            RenderData{
-                //dynamic as this will change frequently
-                planet_data: glium::VertexBuffer::dynamic(display, &surface_contents).unwrap(),
-                
-                indices: glium::IndexBuffer::new(display,glium::index::PrimitiveType::TrianglesList, &surface.triangles).unwrap(),
-
-                scale: 0.01,
-
-                light_pos: LightPosition::Fixed,
-
-                map_mode: MapMode::Natural,
-            },
-
-            surface: surface,
+            surface,
 
             sim_info: 
             SimInfo { 
                 years_per_second: 0.0, 
-                triangulation_interval: 1_000_.0,
+                triangulation_interval: 1_000.0,
                 lapse_rate: gen.lapse_rate,
                 base_temp: gen.base_temp, 
-                axis: axis, 
+                axis, 
                 to_sun: glm::vec3(1.0,0.0,0.0),
-            }
+                aerosols: 0.0,
+                erodibility: DEFAULT_ERODIBILITY,
+                sediment_capacity: DEFAULT_SEDIMENT_CAPACITY,
+                diffusivity: DEFAULT_DIFFUSIVITY,
+                talus_angle: DEFAULT_TALUS_ANGLE,
+            },
+
+            scheduler: scheduler::Scheduler::new(),
         }
     }
 
-    pub fn update(&mut self, deltatime: f32,display:&glium::Display){
-        let years_past = deltatime*self.sim_info.years_per_second;
+    //advances the sim by the real time passed, scaled by years per second
+    //each system runs in its own fixed steps, so the outcome is the same whatever the frame rate
+    pub fn update(&mut self, deltatime: f32){
+        self.advance(deltatime as f64*self.sim_info.years_per_second as f64);
+    }
 
-        self.surface.tectonics(years_past,  &mut self.sim_info);
-        self.surface.temperature(years_past, &self.sim_info);
+    //advances the sim by an amount of years, in fixed steps of each system
+    pub fn advance(&mut self, years_past: f64){
+        self.scheduler.requested += years_past;
 
-        //one year is 360 days here for simplicity
-        self.sim_info.to_sun= glm::rotate_y_vec3(&self.sim_info.to_sun, years_past*(std::f32::consts::PI*2.0));
-        
-        //extract data, needed for rendering, out
-        let surface_contents:Vec<CellData> = self.surface.cells.iter()
-            .map(|c|
-                c.contents
-            )
-            .collect();
-
-        //write surface contents to planet buffer
-        self.render_data.planet_data.write(&surface_contents);
-        //FIX THIS, WORKS BUT BAD
-        self.render_data.indices= glium::IndexBuffer::new(display,glium::index::PrimitiveType::TrianglesList, &self.surface.triangles).unwrap();        ;
+        let tectonics_step = self.scheduler.tectonics.step;
+        for _ in 0..self.scheduler.tectonics.schedule(years_past){
+            self.surface.tectonics(tectonics_step, &mut self.sim_info);
+        }
+
+        let erosion_step = self.scheduler.erosion.step;
+        for _ in 0..self.scheduler.erosion.schedule(years_past){
+            self.surface.hydraulic_erosion(erosion_step, &self.sim_info);
+            self.surface.thermal_erosion(erosion_step, &self.sim_info);
+        }
+
+        let isostasy_step = self.scheduler.isostasy.step;
+        for _ in 0..self.scheduler.isostasy.schedule(years_past){
+            self.surface.isostasy(isostasy_step);
+        }
+
+        let temperature_step = self.scheduler.temperature.step;
+        let temperature_steps = self.scheduler.temperature.schedule(years_past);
+        for _ in 0..temperature_steps{
+            self.orbit(temperature_step);
+            self.surface.temperature(temperature_step, &self.sim_info);
+        }
+        //temp only depends on the current state, so is still updated when paused to show changes to its settings
+        if temperature_steps == 0{
+            self.surface.temperature(0.0, &self.sim_info);
+        }
     }
 
-    pub fn draw(&self, target:&mut glium::Frame, program:&glium::Program, params:&glium::DrawParameters,cam:&graphics::Camera){
-        //turn matrices into type gpu will take
-        let pers:[[f32;4];4] = cam.perspective.into();
-        let view:[[f32;4];4] = cam.view.into();
-        //send light pos corrosponding to user choice
-        let to_light:[f32;3] = match self.render_data.light_pos{
-            LightPosition::Sun=> self.sim_info.to_sun.into(),
-            LightPosition::Camera=> cam.pos.normalize().into(),
-            LightPosition::Fixed=> [0.0,0.0,1.0],
-        };
-
-        let uniform = glium::uniform!{
-            perspective:pers,
-            view: view,
-            to_light: to_light,
-            terra_scale: self.render_data.scale,
-            map_mode: self.render_data.map_mode as i32,
-        };
-
-        target.draw(&self.render_data.planet_data,&self.render_data.indices,program,&uniform,params).unwrap();
+    //advances every system by a given amount of years in one go, ignoring the scheduler
+    pub fn step(&mut self, years: f32){
+        self.surface.tectonics(years,  &mut self.sim_info);
+        self.surface.hydraulic_erosion(years, &self.sim_info);
+        self.surface.thermal_erosion(years, &self.sim_info);
+        self.surface.isostasy(years);
+        self.orbit(years);
+        self.surface.temperature(years, &self.sim_info);
     }
-}
\ No newline at end of file
+
+    //moves the planet around the sun
+    fn orbit(&mut self, years: f32){
+        //one year is 360 days here for simplicity
+        self.sim_info.to_sun= glm::rotate_y_vec3(&self.sim_info.to_sun, years*(std::f32::consts::PI*2.0));
+    }
+}
@@ -0,0 +1,469 @@
+//saving and loading planets to and from a versioned binary file
+//everything is written little endian in the order its read, starting with a header and version number
+//when the format changes bump VERSION, and have the read of whatever changed check the version it is given,
+//filling in anything an older file doesnt have, so old saves are migrated up to the current version on load
+use std::{collections::BTreeSet, fs::File, io::{self, BufReader, BufWriter, Read, Write}, path::Path};
+
+//external crates
+use nalgebra_glm as glm;
+use rand::SeedableRng;
+
+//internal modules
+use crate::graphics::{halfedge::HalfEdgeMesh, shapes::Shape};
+use super::{Planet, SimInfo, scheduler::{Scheduler, SystemClock}, crust::{Crust, CrustType}, events::{Event, EventKind, EventLog, VolcanoKind}, surface::{Cell, CellData, Hotspot, Plate, SimRng, Surface}};
+
+//start of every save file, used to check file is actually a planet
+const MAGIC: [u8;8] = *b"UPSIMPLA";
+//version of the format written by this build
+pub const VERSION: u32 = 1;
+
+//version history
+//1: surface with its crust, plates, hotspots and event log, sim info and scheduler clocks
+
+//anything that can be written to and read from a save
+trait Persist: Sized{
+    fn write(&self, w: &mut impl Write)->io::Result<()>;
+    //version is that of the file being read, so older data can be migrated
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>;
+}
+
+//error for when the data in a file makes no sense
+fn invalid(msg: &str)->io::Error{
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+//primitives, all little endian
+macro_rules! persist_num {
+    ($($t:ty),*) => {$(
+        impl Persist for $t{
+            fn write(&self, w: &mut impl Write)->io::Result<()>{
+                w.write_all(&self.to_le_bytes())
+            }
+            fn read(r: &mut impl Read, _version: u32)->io::Result<Self>{
+                let mut bytes = [0;std::mem::size_of::<$t>()];
+                r.read_exact(&mut bytes)?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+persist_num!(u8, u32, u64, u128, f32, f64);
+
+//usize written as u64 so saves work between platforms
+impl Persist for usize{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        (*self as u64).write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        usize::try_from(u64::read(r, version)?).map_err(|_| invalid("index too large for platform"))
+    }
+}
+
+impl Persist for bool{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        (*self as u8).write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        Ok(u8::read(r, version)? != 0)
+    }
+}
+
+impl<T: Persist> Persist for Option<T>{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.is_some().write(w)?;
+        match self{
+            Some(x)=> x.write(w),
+            None=> Ok(()),
+        }
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        Ok(if bool::read(r, version)? {Some(T::read(r, version)?)} else {None})
+    }
+}
+
+//collections are written as their length followed by every item
+impl<T: Persist> Persist for Vec<T>{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.len().write(w)?;
+        self.iter().try_for_each(|x| x.write(w))
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        let len = usize::read(r, version)?;
+        (0..len).map(|_| T::read(r, version)).collect()
+    }
+}
+
+impl Persist for BTreeSet<usize>{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.len().write(w)?;
+        self.iter().try_for_each(|x| x.write(w))
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        let len = usize::read(r, version)?;
+        (0..len).map(|_| usize::read(r, version)).collect()
+    }
+}
+
+impl<const N: usize> Persist for [f32;N]{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.iter().try_for_each(|x| x.write(w))
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        let mut arr = [0.0;N];
+        for x in arr.iter_mut(){
+            *x = f32::read(r, version)?;
+        }
+        Ok(arr)
+    }
+}
+
+impl Persist for glm::Vec3{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        <[f32;3]>::from(*self).write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        Ok(<[f32;3]>::read(r, version)?.into())
+    }
+}
+
+impl Persist for glm::Mat4{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.iter().try_for_each(|x| x.write(w))
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        Ok(glm::Mat4::from_column_slice(&<[f32;16]>::read(r, version)?))
+    }
+}
+
+//rng stored as its seed, stream and position in stream so it carries on exactly where it left off
+impl Persist for SimRng{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        w.write_all(&self.get_seed())?;
+        self.get_stream().write(w)?;
+        self.get_word_pos().write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        let mut seed = [0;32];
+        r.read_exact(&mut seed)?;
+        let mut rng = SimRng::from_seed(seed);
+        rng.set_stream(u64::read(r, version)?);
+        rng.set_word_pos(u128::read(r, version)?);
+        Ok(rng)
+    }
+}
+
+impl Persist for Shape{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.vertices.write(w)?;
+        self.indices.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        Ok(Shape::new(Vec::read(r, version)?, Vec::read(r, version)?))
+    }
+}
+
+impl Persist for CellData{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.position.write(w)?;
+        self.height.write(w)?;
+        self.humidity.write(w)?;
+        self.water.write(w)?;
+        self.temperature.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        Ok(CellData{
+            position: Persist::read(r, version)?,
+            height: Persist::read(r, version)?,
+            humidity: Persist::read(r, version)?,
+            water: Persist::read(r, version)?,
+            temperature: Persist::read(r, version)?,
+        })
+    }
+}
+
+impl Persist for Cell{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.contents.write(w)?;
+        self.position.write(w)?;
+        self.plate.write(w)?;
+        self.base_index.write(w)?;
+        self.crust.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        let contents:CellData = Persist::read(r, version)?;
+        let position = Persist::read(r, version)?;
+        let plate:Option<usize> = Persist::read(r, version)?;
+        let base_index = Persist::read(r, version)?;
+        let crust = Persist::read(r, version)?;
+        Ok(Cell { contents, position, plate, base_index, crust })
+    }
+}
+
+impl Persist for Crust{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        let kind:u8 = match self.kind{
+            CrustType::Continental=> 0,
+            CrustType::Oceanic=> 1,
+        };
+        kind.write(w)?;
+        self.thickness.write(w)?;
+        self.density.write(w)?;
+        self.age.write(w)?;
+        self.origin.write(w)?;
+        self.swell.write(w)?;
+        self.strain.write(w)?;
+        self.rupture.write(w)?;
+        self.hazard.write(w)?;
+        self.eruption.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        let kind = match u8::read(r, version)?{
+            0=> CrustType::Continental,
+            1=> CrustType::Oceanic,
+            _=> return Err(invalid("unknown crust type")),
+        };
+        Ok(Crust{
+            kind,
+            thickness: Persist::read(r, version)?,
+            density: Persist::read(r, version)?,
+            age: Persist::read(r, version)?,
+            origin: Persist::read(r, version)?,
+            swell: Persist::read(r, version)?,
+            strain: Persist::read(r, version)?,
+            rupture: Persist::read(r, version)?,
+            hazard: Persist::read(r, version)?,
+            eruption: Persist::read(r, version)?,
+        })
+    }
+}
+
+impl Persist for Plate{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.axis.write(w)?;
+        self.density.write(w)?;
+        self.speed.write(w)?;
+        self.translation.write(w)?;
+        self.subducted.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        Ok(Plate{
+            axis: Persist::read(r, version)?,
+            density: Persist::read(r, version)?,
+            speed: Persist::read(r, version)?,
+            translation: Persist::read(r, version)?,
+            subducted: Persist::read(r, version)?,
+        })
+    }
+}
+
+impl Persist for Hotspot{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.position.write(w)?;
+        self.strength.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        Ok(Hotspot{
+            position: Persist::read(r, version)?,
+            strength: Persist::read(r, version)?,
+        })
+    }
+}
+
+impl Persist for Event{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        let kind:u8 = match self.kind{
+            EventKind::Earthquake=> 0,
+            EventKind::Eruption(VolcanoKind::Arc)=> 1,
+            EventKind::Eruption(VolcanoKind::Rift)=> 2,
+            EventKind::Eruption(VolcanoKind::Hotspot)=> 3,
+        };
+        kind.write(w)?;
+        self.position.write(w)?;
+        self.magnitude.write(w)?;
+        self.time.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        let kind = match u8::read(r, version)?{
+            0=> EventKind::Earthquake,
+            1=> EventKind::Eruption(VolcanoKind::Arc),
+            2=> EventKind::Eruption(VolcanoKind::Rift),
+            3=> EventKind::Eruption(VolcanoKind::Hotspot),
+            _=> return Err(invalid("unknown event type")),
+        };
+        Ok(Event{
+            kind,
+            position: Persist::read(r, version)?,
+            magnitude: Persist::read(r, version)?,
+            time: Persist::read(r, version)?,
+        })
+    }
+}
+
+//written as a list of events, oldest first
+impl Persist for EventLog{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.iter().copied().collect::<Vec<Event>>().write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        let mut log = EventLog::default();
+        for event in Vec::<Event>::read(r, version)?{
+            log.push(event);
+        }
+        Ok(log)
+    }
+}
+
+impl Persist for Surface{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.base_mesh.write(w)?;
+        self.cells.write(w)?;
+        self.mesh.slots().write(w)?;
+        self.plates.write(w)?;
+        self.bank.write(w)?;
+        self.cell_distance.write(w)?;
+        self.since_triangulation.write(w)?;
+        self.plate_seed.write(w)?;
+        self.rng.write(w)?;
+        self.hotspots.write(w)?;
+        self.time.write(w)?;
+        self.events.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        let base_mesh:Shape = Persist::read(r, version)?;
+        let cells:Vec<Cell> = Persist::read(r, version)?;
+        let triangles:Vec<u32> = Persist::read(r, version)?;
+        //make sure nothing points outside of what was loaded, as that would panic later in the sim
+        if triangles.iter().any(|i| *i as usize >= cells.len()){
+            return Err(invalid("planet references cells that dont exist"));
+        }
+        let mut surface = Surface{
+            //connectivity isnt saved as it can be rebuilt from the triangles
+            base_topology: HalfEdgeMesh::from_shape(&base_mesh),
+            base_mesh,
+            mesh: HalfEdgeMesh::from_slots(cells.len(), &triangles),
+            cells,
+            plates: Persist::read(r, version)?,
+            bank: Persist::read(r, version)?,
+            cell_distance: Persist::read(r, version)?,
+            since_triangulation: Persist::read(r, version)?,
+            plate_seed: Persist::read(r, version)?,
+            rng: Persist::read(r, version)?,
+            hotspots: Persist::read(r, version)?,
+            time: Persist::read(r, version)?,
+            events: Persist::read(r, version)?,
+            //boundaries arent saved either, as they follow from the plates
+            boundaries: Vec::new(),
+        };
+        if surface.cells.iter().any(|c| c.plate.is_some_and(|p| p >= surface.plates.len())){
+            return Err(invalid("planet references plates that dont exist"));
+        }
+        surface.classify_boundaries();
+        Ok(surface)
+    }
+}
+
+impl Persist for SimInfo{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.years_per_second.write(w)?;
+        self.triangulation_interval.write(w)?;
+        self.lapse_rate.write(w)?;
+        self.base_temp.write(w)?;
+        self.axis.write(w)?;
+        self.to_sun.write(w)?;
+        self.aerosols.write(w)?;
+        self.erodibility.write(w)?;
+        self.sediment_capacity.write(w)?;
+        self.diffusivity.write(w)?;
+        self.talus_angle.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        Ok(SimInfo{
+            years_per_second: Persist::read(r, version)?,
+            triangulation_interval: Persist::read(r, version)?,
+            lapse_rate: Persist::read(r, version)?,
+            base_temp: Persist::read(r, version)?,
+            axis: Persist::read(r, version)?,
+            to_sun: Persist::read(r, version)?,
+            aerosols: Persist::read(r, version)?,
+            erodibility: Persist::read(r, version)?,
+            sediment_capacity: Persist::read(r, version)?,
+            diffusivity: Persist::read(r, version)?,
+            talus_angle: Persist::read(r, version)?,
+        })
+    }
+}
+
+impl Persist for SystemClock{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.step.write(w)?;
+        self.max_steps.write(w)?;
+        self.pending.write(w)?;
+        self.simulated.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        let mut clock = SystemClock::new(Persist::read(r, version)?, Persist::read(r, version)?);
+        clock.pending = Persist::read(r, version)?;
+        clock.simulated = Persist::read(r, version)?;
+        Ok(clock)
+    }
+}
+
+impl Persist for Scheduler{
+    fn write(&self, w: &mut impl Write)->io::Result<()>{
+        self.requested.write(w)?;
+        self.tectonics.write(w)?;
+        self.temperature.write(w)?;
+        self.isostasy.write(w)?;
+        self.erosion.write(w)
+    }
+    fn read(r: &mut impl Read, version: u32)->io::Result<Self>{
+        Ok(Scheduler{
+            requested: Persist::read(r, version)?,
+            tectonics: Persist::read(r, version)?,
+            temperature: Persist::read(r, version)?,
+            isostasy: Persist::read(r, version)?,
+            erosion: Persist::read(r, version)?,
+        })
+    }
+}
+
+impl Planet{
+    //writes the whole planet, with header, to something
+    pub fn write_to(&self, w: &mut impl Write)->io::Result<()>{
+        w.write_all(&MAGIC)?;
+        VERSION.write(w)?;
+        self.surface.write(w)?;
+        self.sim_info.write(w)?;
+        self.scheduler.write(w)
+    }
+
+    //reads a planet written by this or any older version
+    pub fn read_from(r: &mut impl Read)->io::Result<Planet>{
+        let mut magic = [0;8];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC{
+            return Err(invalid("not a planet save file"));
+        }
+        let version = u32::read(r, VERSION)?;
+        if version == 0 || version > VERSION{
+            return Err(invalid(&format!("unsupported save version {}, this build reads up to {}", version, VERSION)));
+        }
+        Ok(Planet{
+            surface: Surface::read(r, version)?,
+            sim_info: SimInfo::read(r, version)?,
+            scheduler: Scheduler::read(r, version)?,
+        })
+    }
+
+    //saves planet to file at path
+    pub fn save(&self, path: impl AsRef<Path>)->io::Result<()>{
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_to(&mut w)?;
+        w.flush()
+    }
+
+    //loads planet from file at path
+    pub fn load(path: impl AsRef<Path>)->io::Result<Planet>{
+        Planet::read_from(&mut BufReader::new(File::open(path)?))
+    }
+}
@@ -0,0 +1,74 @@
+//runs each system in fixed steps of geological time, so results dont depend on frame rate
+//real time passed each frame is turned into years owed to every system, which then run as many whole steps as they are owed
+//each system can only run so many steps per frame, anything more is dropped and the system marked as falling behind
+
+//timing for a single system
+#[derive(Clone)]
+pub struct SystemClock{
+    //years simulated by each step of the system
+    pub step: f32,
+    //most steps the system may run in one frame
+    pub max_steps: u32,
+    //years owed to the system but not yet simulated, always less than a step after a frame
+    pub pending: f64,
+    //total years the system has simulated
+    pub simulated: f64,
+    //whether time had to be dropped last frame as the system couldnt keep up
+    pub behind: bool,
+}
+impl SystemClock{
+    pub fn new(step:f32, max_steps:u32)->SystemClock{
+        SystemClock { step, max_steps, pending: 0.0, simulated: 0.0, behind: false }
+    }
+
+    //adds years owed, returns amount of steps to run now, dropping any owed time beyond the cap
+    pub fn schedule(&mut self, years:f64)->u32{
+        self.pending += years;
+        let owed_steps = (self.pending/self.step as f64).floor();
+        self.behind = owed_steps > self.max_steps as f64;
+        let steps = owed_steps.min(self.max_steps as f64);
+        //anything left over beyond a step when behind is dropped so the backlog doesnt grow forever
+        self.pending = if self.behind {0.0} else {self.pending-steps*self.step as f64};
+        self.simulated += steps*self.step as f64;
+        steps as u32
+    }
+}
+
+//clocks for every system the planet runs
+#[derive(Clone)]
+pub struct Scheduler{
+    //total years that have been asked to pass
+    pub requested: f64,
+    pub tectonics: SystemClock,
+    pub temperature: SystemClock,
+    pub isostasy: SystemClock,
+    pub erosion: SystemClock,
+}
+impl Scheduler{
+    pub fn new()->Scheduler{
+        Scheduler{
+            requested: 0.0,
+            //plates move so little per year that large steps are fine
+            tectonics: SystemClock::new(1_000.0, 16),
+            //monthly so the seasons still show
+            temperature: SystemClock::new(1.0/12.0, 4),
+            //the mantle flows slowly, so this can be as coarse as tectonics
+            isostasy: SystemClock::new(1_000.0, 16),
+            //rivers wear the land down slowly too
+            erosion: SystemClock::new(1_000.0, 16),
+        }
+    }
+
+    //names of systems that couldnt keep up last frame
+    pub fn behind(&self)->Vec<&'static str>{
+        [("Tectonics",&self.tectonics),("Temperature",&self.temperature),("Isostasy",&self.isostasy),("Erosion",&self.erosion)].into_iter()
+            .filter(|(_,clock)| clock.behind)
+            .map(|(name,_)| name)
+            .collect()
+    }
+}
+impl Default for Scheduler{
+    fn default()->Self{
+        Scheduler::new()
+    }
+}
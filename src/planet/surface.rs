@@ -1,17 +1,22 @@
 
 //external crates
-use std::{vec,collections::{HashMap,HashSet}};
-use noise::{NoiseFn, Perlin, Seedable};
+use std::collections::BTreeSet;
+use noise::Perlin;
 use nalgebra_glm as glm;
-use rand::{Rng, seq::SliceRandom, rngs::ThreadRng};
+use rand::{Rng, SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
 
 //internal crates
-use crate::graphics::shapes::{self, Shape};
-use super::{GenInfo, SimInfo,utils::*};
+use crate::graphics::{halfedge::HalfEdgeMesh, shapes::{self, Shape}};
+use super::{GenInfo,adjacency::Adjacency,boundary::BoundaryEdge,crust::{Crust, RIDGE_HEIGHT},events::EventLog,utils::*};
 
 
+//random generator used by everything in the sim, seedable so a given seed always gives the same planet
+pub type SimRng = ChaCha8Rng;
+
 //data for each cell on the planet, this data is sent to gpu
 #[derive(Copy, Clone)]
+#[derive(PartialEq)]
 pub struct CellData {
     //position in space of cell
     pub position: [f32;3],
@@ -28,18 +33,20 @@ glium::implement_vertex!(CellData,position,height,humidity,water,temperature);
 
 //data for every plate
 pub struct Plate{
-    //axis around which the plate rotates
+    //euler pole the plate rotates around, moved by the forces on the plate
     pub axis: glm::Vec3,
-    //density of plates determines which will overlap another and nature of collision
+    //average density of the plates crust, in g/cm^3, kept up to date by the sim
     pub density: f32,
     //cm per year, avg is 5-15, earth rad = 6,371km,
     pub speed: f32,
     //matrix recording the translation of all points in this plates in respect to their original, base positions
-    pub translation: glm::TMat4<f32>
+    pub translation: glm::TMat4<f32>,
+    //amount of cells worth of crust the plate has lost to subduction
+    pub subducted: f32,
 }
 impl Plate{
     //creates new random plate
-    pub fn random(rng:&mut ThreadRng)->Plate{
+    pub fn random(rng:&mut SimRng)->Plate{
         //randomized axis the plate moves around
         let rand_axis = {
             let x:f32 = rng.gen_range(-std::f32::consts::PI..=std::f32::consts::PI);
@@ -50,13 +57,50 @@ impl Plate{
 
         Plate {
             axis: rand_axis,
-            density: rng.gen_range(0.0..10.0),//not representative of real value, just used to compare plates
+            density: 0.0,//filled in from the crust once the plate has cells
             speed: rng.gen_range(0.00..0.2)/6371000.0, //done in meters per second, 6371000 is earths radius
             translation: glm::identity(),//sets translation as identity as upon creation of plate there is no translation
+            subducted: 0.0,
+        }
+    }
+}
+
+//mantle plume rising under the surface, fixed in place while plates move over it
+pub struct Hotspot{
+    //point on the surface the plume rises under
+    pub position: glm::Vec3,
+    //km of volcanic rock piled up every million years right above the plume
+    pub strength: f32,
+}
+impl Hotspot{
+    //creates hotspot at a random point, with strength varying around the given one
+    pub fn random(rng:&mut SimRng,strength:f32)->Hotspot{
+        Hotspot {
+            position: random_direction(rng),
+            strength: strength*rng.gen_range(0.5..1.5),
         }
     }
 }
 
+//random point on the unit sphere, uniform by picking height and angle around the y axis
+fn random_direction(rng:&mut SimRng)->glm::Vec3{
+    let y:f32 = rng.gen_range(-1.0..=1.0);
+    let angle:f32 = rng.gen_range(0.0..std::f32::consts::TAU);
+    let ring = (1.0-y*y).sqrt();
+    glm::vec3(ring*angle.cos(), y, ring*angle.sin())
+}
+
+//id a plate has after absorb is merged into keep and removed
+pub fn merged_id(plate:usize,keep:usize,absorb:usize)->usize{
+    let plate = if plate == absorb {keep} else {plate};
+    if plate > absorb {plate-1} else {plate}
+}
+
+//fewest cells a plate can have and still be split
+const MIN_SPLIT: usize = 8;
+//how many lines are tried when looking for where a plate is weakest
+const RIFT_CANDIDATES: usize = 8;
+
 //data relating to the cell
 pub struct Cell{
     //what is contained within the cell
@@ -66,9 +110,35 @@ pub struct Cell{
     //plate that the cell belongs too
     pub plate: Option<usize>,
     //index of cell on the original stored mesh
-    pub base_index: u32
+    pub base_index: u32,
+    //rock the cell sits on
+    pub crust: Crust,
 }
 impl Cell{
+    //sets height, keeping it in the -10km to 10km range
+    pub fn set_height(&mut self,height:f32){
+        self.contents.height = height.clamp(-10.0, 10.0);
+    }
+
+    //raises the surface by an amount, thickening the crust under it so it stays up
+    pub fn uplift(&mut self,height:f32){
+        self.set_height(self.contents.height+height);
+        self.crust.support(height);
+    }
+
+    //wears an amount of km off the surface, taking the crust with it
+    //less crust floats lower, but by much less than was taken, so the surface rebounds most of the way as isostasy catches up
+    pub fn erode(&mut self,depth:f32){
+        self.set_height(self.contents.height-depth);
+        self.crust.thickness = (self.crust.thickness-depth).max(0.0);
+    }
+
+    //piles an amount of km of sediment onto the surface, which becomes part of the crust
+    pub fn deposit(&mut self,depth:f32){
+        self.set_height(self.contents.height+depth);
+        self.crust.thickness += depth;
+    }
+
     //creates effectivly blank cell at pos
     pub fn new(pos:glm::Vec3,base_index:u32,plate: Option<usize>)->Cell{
         Cell { 
@@ -82,8 +152,19 @@ impl Cell{
             position:pos,
             plate,
             base_index,
+            crust: Crust::oceanic(0.0, plate),
         }
     }
+
+    //creates new oceanic crust at a mid ocean ridge, taking on the humidity of the cells it spreads from
+    pub fn ridge(pos:glm::Vec3,base_index:u32,plate: Option<usize>,humidity:f32)->Cell{
+        let mut cell = Cell::new(pos, base_index, plate);
+        cell.set_height(RIDGE_HEIGHT);
+        //ridges are always under the sea
+        cell.contents.water = 1.0;
+        cell.contents.humidity = humidity;
+        cell
+    }
     //creates a new cell with perlin noise
     pub fn from_perlin(position:glm::Vec3,base_index: u32,plate: Option<usize>,perlin: Perlin)->Cell{
         let height = octive_noise(perlin, &position, 2.5, 7, 0.6, 2.5)*10.0;
@@ -101,7 +182,8 @@ impl Cell{
             },
             position,
             plate,
-            base_index
+            base_index,
+            crust: Crust::from_height(height, plate),
         }   
     }
 }
@@ -110,26 +192,38 @@ impl Cell{
 pub struct Surface{
     //base mesh containing the orignal planet shape
     pub base_mesh: Shape,
+    //connectivity of the base mesh, for finding where new cells go
+    pub base_topology: HalfEdgeMesh,
     //every cell
     pub cells: Vec<Cell>,
-    //triangle data, u32 as thats whats needed for passing to gpu
-    pub triangles: Vec<u32>,
+    //triangles connecting cells, use indices() for what gets passed to the gpu, and adjacency() for which cells are connected
+    pub mesh: HalfEdgeMesh,
     //all tectonic plates on the surface
     pub plates: Vec<Plate>,
-    //contains indices of all cells not in use
-    pub bank: HashSet<usize>,
+    //plumes in the mantle, which dont move with the plates
+    pub hotspots: Vec<Hotspot>,
+    //every edge between cells on different plates, classified by how the plates move, kept up to date by the sim
+    pub boundaries: Vec<BoundaryEdge>,
+    //years the surface has been simulated for
+    pub time: f64,
+    //newest earthquakes and the like
+    pub events: EventLog,
+    //contains indices of all cells not in use, ordered so taking from it is deterministic
+    pub bank: BTreeSet<usize>,
     //distace used for cell collisions, absolute closest one can be to another before one gets destroyed
     pub cell_distance: f32,
     //time passed since last triangulation
     pub since_triangulation:f32,
+    //seed the current plates were generated from
+    pub plate_seed: u64,
     //random generator for surface
-    pub rng: ThreadRng,
+    pub rng: SimRng,
 }
 impl Surface{
     pub fn new(shape: shapes::Shape,gen: &GenInfo)->Surface{
-        let mut rng = rand::thread_rng();
+        let mut rng = SimRng::seed_from_u64(gen.seed as u64);
         //creates cells for surface
-        let mut cells:Vec<Cell> = {
+        let cells:Vec<Cell> = {
             let perlin = Perlin::new(gen.seed);
             //generates cells with perlin noise
             shape.vertices.clone().into_iter().zip(0..shape.vertices.len())
@@ -138,37 +232,55 @@ impl Surface{
             )
             .collect()
         };
-        //get edges from mesh
-        let edges = indices_to_edges(&shape.indices);
+        //store triangles for planet, and which cells they connect
+        let mesh = HalfEdgeMesh::from_shape(&shape);
 
         //creates randomized plates for surface
-        let mut plates:Vec<Plate> = (0..gen.plate_no)
+        let plates:Vec<Plate> = (0..gen.plate_no)
         .map(|_|{
             Plate::random(&mut rng)
         })
         .collect();
 
         //length of edge to be used to determine collision
-        let cell_distance = (cells[edges[0].0].position - cells[edges[0].1].position).magnitude();
+        let edge = Adjacency::new(&mesh).edges().next().unwrap();
+        let cell_distance = (cells[edge.0].position - cells[edge.1].position).magnitude();
         //bank for recording unused vertices
-        let bank = HashSet::with_capacity(shape.vertices.len()/2);
-        //store triangles for planet
-        let triangles = shape.indices.clone();
+        let bank = BTreeSet::new();
 
         let mut surface = Surface{
+            base_topology: HalfEdgeMesh::from_shape(&shape),
             base_mesh: shape,
             cells,
-            triangles,
+            mesh,
             plates,
+            hotspots: Vec::new(),
+            boundaries: Vec::new(),
+            time: 0.0,
+            events: EventLog::default(),
             bank,
             cell_distance,
             since_triangulation:0.0,
+            plate_seed: gen.seed as u64,
             rng,
         };
         surface.fill_plates();
+        surface.hotspots = (0..gen.hotspot_no)
+            .map(|_| Hotspot::random(&mut surface.rng, gen.hotspot_strength))
+            .collect();
         surface
     }
 
+    //replaces plates with new ones generated from the given seed, leaving terrain untouched
+    pub fn reseed_plates(&mut self,seed:u64){
+        self.plate_seed = seed;
+        self.rng = SimRng::seed_from_u64(seed);
+        self.plates = (0..self.plates.len())
+            .map(|_| Plate::random(&mut self.rng))
+            .collect();
+        self.fill_plates();
+    }
+
     //generates specified number of plates
     pub fn fill_plates(&mut self){
         if !self.plates.is_empty(){
@@ -176,7 +288,7 @@ impl Surface{
             self.cells.iter_mut()
                 .for_each(|c| c.plate = None);
             //get edges to be used to extend plates accross
-            let edges = indices_to_edges(&self.triangles);
+            let edges:Vec<(usize,usize)> = self.adjacency().edges().collect();
             //place seed cells randomly for each plate for each to spread out from
             for plate in 0..self.plates.len(){
                 let target = self.rng.gen_range(0..self.cells.len());
@@ -184,96 +296,238 @@ impl Surface{
             }
             //fill planet with the plates via random fill, if there are plates to even fill with
             while (0..self.cells.len()).into_iter().any(|c| 
-                !self.cells[c].plate.is_some()&&
+                self.cells[c].plate.is_none()&&
                 !self.bank.contains(&c)){
                 //get all plate boundries
                 let plate_boundries:Vec<&(usize,usize)> = edges.iter()
                     .filter(|e| 
-                    &self.cells[e.0].plate != &self.cells[e.1].plate)
+                    self.cells[e.0].plate != self.cells[e.1].plate)
                     .collect();
                 let extend_no = usize::max(plate_boundries.len()/8, 1);
                 //extend plate across 1/8 of boundries randomly
                 for _ in 0..extend_no{
                     let edge = plate_boundries.choose(&mut self.rng).unwrap();
-                    if self.cells[edge.0].plate == None{
+                    if self.cells[edge.0].plate.is_none(){
                         self.cells[edge.0].plate = self.cells[edge.1].plate;
-                    }else if self.cells[edge.1].plate == None{
+                    }else if self.cells[edge.1].plate.is_none(){
                         self.cells[edge.1].plate = self.cells[edge.0].plate;
                     }
                 }
             }
+            //crust is taken to have formed on whichever plate it starts on
+            for cell in self.cells.iter_mut(){
+                cell.crust.origin = cell.plate;
+            }
         }
+        self.update_plate_densities();
+        self.classify_boundaries();
     }
 
-    //remove cell from mesh
-    pub fn remove_cell(&mut self,cell: usize,provoking: usize){
-        //hashset to ensure surrounding points are unique
-        //let mut surrounding_points:HashSet<u32> = HashSet::with_capacity(6);
-        let mut surrounding_tris:Vec<u32> = Vec::with_capacity(16);
-
-        //filter out triangles that contain cell, record all other points if they do
-        self.triangles = self.triangles.chunks(3)
-            .filter(|chunk| {
-                //get only the triangles(chunks of 3 indices) which do not contain the target cell
-                if !chunk.contains(&(cell as u32)){
-                    true
-                }else{
-                    //add all surrounding points that arent the cell itself to hashset for triangulation
-                    surrounding_tris.append(
-                       &mut chunk.iter()
-                        .map(|i| *i)
-                        .collect());
-                    false
-                }
+    //splits a plate in two along a plane through its middle, the smaller side becoming a new plate with its own motion
+    //a few planes are tried, splitting along whichever the crust is thinnest and so weakest
+    //returns the new plate and how many cells moved onto it, none if the plate was too small to split
+    pub fn split_plate(&mut self,plate:usize)->Option<(usize,usize)>{
+        let cells:Vec<usize> = (0..self.cells.len())
+            .filter(|c| self.cells[*c].plate == Some(plate) && !self.bank.contains(c))
+            .collect();
+        if cells.len() < MIN_SPLIT{
+            return None;
+        }
+        let centre = cells.iter().map(|c| self.cells[*c].position).sum::<glm::Vec3>().normalize();
+        let mut weakest:Option<(f32,glm::Vec3)> = None;
+        for _ in 0..RIFT_CANDIDATES{
+            let direction = random_direction(&mut self.rng);
+            let normal = (direction-centre*glm::dot(&direction,&centre)).normalize();
+            //cells close enough to the plane to be on the rift
+            let along:Vec<f32> = cells.iter()
+                .filter(|c| glm::dot(&self.cells[**c].position,&normal).abs() < self.cell_distance)
+                .map(|c| self.cells[*c].crust.thickness)
+                .collect();
+            if along.is_empty(){
+                continue;
+            }
+            let thickness = along.iter().sum::<f32>()/along.len() as f32;
+            if weakest.is_none_or(|w| thickness < w.0){
+                weakest = Some((thickness,normal));
+            }
+        }
+        let normal = weakest?.1;
+        let (side,rest):(Vec<usize>,Vec<usize>) = cells.iter().partition(|c| glm::dot(&self.cells[**c].position,&normal) > 0.0);
+        let moving = if side.len() < rest.len() {side} else {rest};
+        if moving.is_empty(){
+            return None;
+        }
+        let mut new = Plate::random(&mut self.rng);
+        new.translation = self.plates[plate].translation;
+        self.plates.push(new);
+        let new = self.plates.len()-1;
+        for c in moving.iter(){
+            self.cells[*c].plate = Some(new);
+        }
+        self.update_plate_densities();
+        Some((new,moving.len()))
+    }
+
+    //folds one plate into another, moving its cells over and removing it
+    //plates after the absorbed one move down an index, so cells and crust are remapped to match
+    pub fn merge_plates(&mut self,keep:usize,absorb:usize){
+        if keep == absorb{
+            return;
+        }
+        let remap = |p:usize| merged_id(p, keep, absorb);
+        for cell in self.cells.iter_mut(){
+            cell.plate = cell.plate.map(remap);
+            cell.crust.origin = cell.crust.origin.map(remap);
+        }
+        let absorbed = self.plates.remove(absorb);
+        self.plates[remap(keep)].subducted += absorbed.subducted;
+        self.update_plate_densities();
+    }
+
+    //how many cells in the mesh each plate has, counted in one go
+    pub fn plate_sizes(&self)->Vec<usize>{
+        let mut sizes = vec![0;self.plates.len()];
+        for (i,cell) in self.cells.iter().enumerate(){
+            if let Some(plate) = cell.plate.filter(|_| !self.bank.contains(&i)){
+                sizes[plate] += 1;
+            }
+        }
+        sizes
+    }
+
+    //sets each plates density to the average of its crust, plates without cells are left as they are
+    pub fn update_plate_densities(&mut self){
+        let mut totals:Vec<(f32,usize)> = vec![(0.0,0);self.plates.len()];
+        for (i,cell) in self.cells.iter().enumerate(){
+            if let (Some(plate),false) = (cell.plate,self.bank.contains(&i)){
+                totals[plate].0 += cell.crust.density;
+                totals[plate].1 += 1;
+            }
+        }
+        for (plate,(total,count)) in self.plates.iter_mut().zip(totals){
+            if count > 0{
+                plate.density = total/count as f32;
+            }
+        }
+    }
+
+    //which cells are connected, from the mesh
+    pub fn adjacency(&self)->Adjacency<'_>{
+        Adjacency::new(&self.mesh)
+    }
+
+    //cell in the mesh closest to a point, found by walking towards it from any cell in the mesh
+    //none if the mesh has no cells at all
+    pub fn nearest_cell(&self,position:&glm::Vec3)->Option<usize>{
+        let distance = |c:usize| (self.cells[c].position-position).magnitude();
+        let mut current = (0..self.cells.len()).find(|c| !self.bank.contains(c))?;
+        while let Some(next) = self.adjacency().neighbours(current)
+            .filter(|n| distance(*n) < distance(current))
+            .min_by(|a,b| distance(*a).total_cmp(&distance(*b))){
+            current = next;
+        }
+        Some(current)
+    }
+
+    //whether a triangle is wound counter clockwise seen from outside the planet, like the rest of the mesh
+    pub fn faces_outwards(&self,tri:&[u32])->bool{
+        let [a,b,c] = [tri[0],tri[1],tri[2]].map(|i| self.cells[i as usize].position);
+        glm::dot(&glm::cross(&(b-a),&(c-a)),&(a+b+c)) > 0.0
+    }
+
+    //triangulates a hole in the mesh by ear clipping its outline flat, wound like the rest of the mesh
+    //none if it cant be, e.g. if the outline crosses itself once flattened
+    pub fn triangulate_outline(&self,polygon:&[usize])->Option<Vec<u32>>{
+        //project outline flat, centered on the hole
+        let positions:Vec<glm::Vec3> = polygon.iter().map(|c| self.cells[*c].position).collect();
+        let pole = positions.iter().sum::<glm::Vec3>().normalize();
+        let points = stereographic_project(&positions, pole);
+        //projecting can flip the outline, ear clipping needs it counter clockwise
+        let mut order:Vec<usize> = (0..polygon.len()).collect();
+        if signed_area(&points) < 0.0{
+            order.reverse();
+        }
+        let triangles = ear_clip(&points, order)?.chunks(3)
+            .flat_map(|t| {
+                let tri = [polygon[t[0] as usize] as u32,polygon[t[1] as usize] as u32,polygon[t[2] as usize] as u32];
+                if self.faces_outwards(&tri) {tri} else {[tri[0],tri[2],tri[1]]}
             })
-            .flatten()
-            .map(|n|*n)
             .collect();
+        Some(triangles)
+    }
+
+    //remove cell from mesh, filling the hole by connecting its neighbours to the provoking cell
+    //if that would fold any triangles over, as not all of the hole can be seen from the provoking cell, the hole is ear clipped instead
+    //the cell keeps its crust until it is reused, so whatever removed it can still pass the crust on
+    pub fn remove_cell(&mut self,cell: usize,provoking: usize){
+        let mut fill = self.mesh.fan(cell as u32, provoking as u32);
+        if !fill.chunks(3).all(|t| self.faces_outwards(t)){
+            let around:Vec<u32> = self.mesh.vertex_faces(cell as u32).flatten().collect();
+            //a cell on the edge of a hole is part of its own outline, and then the fan is all there is
+            if let Some(clipped) = triangles_to_polygon(&around)
+                .filter(|outline| !outline.contains(&cell))
+                .and_then(|outline| self.triangulate_outline(&outline)){
+                fill = clipped;
+            }
+        }
+        self.mesh.remove_vertex(cell as u32, &fill);
+    }
 
-        //gets new triangulation 
-        let mut triangulation =  connect_point(surrounding_tris, provoking as u32);
-        //adds new triangles
-        self.triangles.append(&mut triangulation);
+    //puts a cell not in the mesh into whichever of the given triangles it lies in, splitting it in 3
+    //triangles is updated with the ones replacing it, returns false if it wasnt in any of them
+    pub fn insert_cell(&mut self,cell: usize,triangles: &mut Vec<u32>)->bool{
+        let position = self.cells[cell].position;
+        let containing = triangles.chunks(3)
+            .position(|t| {
+                let corners:Vec<glm::Vec3> = t.iter().map(|i| self.cells[*i as usize].position).collect();
+                in_spherical_triangle(&corners, &position)
+            });
+        let t = match containing{
+            Some(t)=> t,
+            None=> return false,
+        };
+        match self.mesh.insert_vertex([triangles[t*3],triangles[t*3+1],triangles[t*3+2]], cell as u32){
+            Some(change)=> {
+                triangles.splice(t*3..t*3+3, change.added);
+                true
+            },
+            None=> false,
+        }
     }
 
     //adds a new cell to planet using a provoking edge belonging to one plate
     pub fn add_cell(&mut self,edge:(usize,usize),cell:usize){
-        //get index of new pos
-        let third_index = self.third_point(edge);
-        //use index of new point to get pos of new point
-        if let Some(index) = third_index {
-            let plate = self.cells[edge.0].plate;
-            //use index to find new pos, transform by plate
-            let mut pos = self.base_mesh.vertices[index as usize];
-            pos = (self.plates[plate.unwrap()].translation*glm::vec3_to_vec4(&pos)).xyz();
+        if let Some((pos,index)) = self.cell_completing(edge){
             //use new pos to create new cell in same plate as edge
-            self.cells[cell] = Cell::new(pos, index, self.cells[edge.0].plate);
+            self.cells[cell] = self.ridge_cell(edge, pos, index);
             //put new cell into planet mesh by connecting to provoking edge
-            self.triangles.append(&mut vec![edge.0 as u32,edge.1 as u32,cell as u32]);
+            let tri = [edge.0 as u32,edge.1 as u32,cell as u32];
+            self.mesh.add_face(tri);
         }
     }
 
+    //new crust spreading out from an edge, in the edges plate
+    pub fn ridge_cell(&self,edge:(usize,usize),pos:glm::Vec3,index:u32)->Cell{
+        let humidity = (self.cells[edge.0].contents.humidity+self.cells[edge.1].contents.humidity)/2.0;
+        Cell::ridge(pos, index, self.cells[edge.0].plate, humidity)
+    }
+
+    //position and base index of the point that would complete the triangle on an edge of one plate, according to the base mesh
+    pub fn cell_completing(&self,edge:(usize,usize))->Option<(glm::Vec3,u32)>{
+        //get index of new pos
+        let index = self.third_point(edge)?;
+        self.cells[edge.0].plate?;
+        //move the new pos the same way the edge has moved since it was in the base mesh
+        //found from the edge itself rather than its plate, as cells can change plate when plates split and merge
+        let (a,b) = (&self.cells[edge.0],&self.cells[edge.1]);
+        let base = |i:u32| self.base_mesh.vertices[i as usize];
+        let rotation = edge_frame(&a.position, &b.position)*edge_frame(&base(a.base_index), &base(b.base_index)).transpose();
+        Some((rotation*base(index),index))
+    }
+
     //takes edge on planet and returns the index of the 3rd point in the triangle that should exist according to base mesh
     pub fn third_point(&self,edge:(usize,usize))->Option<u32>{
-        //get base indices of edge
-        let base_edge = (self.cells[edge.0].base_index,self.cells[edge.1].base_index);
-        //use base indices to search base mesh for triangle that edge exists in 
-        //and return index of the third point
-        self.base_mesh.indices.chunks(3)
-            .find_map(|t| {
-                //skip tri if doesnt contain both
-                if !t.contains(&base_edge.0)||!t.contains(&base_edge.0){
-                    return None;
-                }
-                //go through each edge on tri
-                for i in 0..3{
-                    //check if edge is anywhere in triangle
-                    if t[i] == base_edge.0 && t[(i+1)%3] == base_edge.1{
-                        //return third point
-                        return Some(t[(i+2)%3]);
-                    }
-                }
-                None
-            })
+        //get base indices of edge, the third point is opposite it in the base mesh
+        self.base_topology.opposite(self.cells[edge.0].base_index, self.cells[edge.1].base_index)
     }
 }
\ No newline at end of file
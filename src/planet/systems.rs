@@ -1,30 +1,138 @@
 //managing all the planets systems
 //implimented on surface in new module for better structuring
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 //external crates
 use nalgebra_glm as glm;
+use rand::Rng;
 //internal modules
-use super::{SimInfo,utils::*};
+use super::{SimInfo, PLANET_RADIUS, boundary::{BoundaryEdge, BoundaryType}, crust, events::{Event, EventKind, VolcanoKind}, surface::merged_id, utils::*};
+
+//converging boundary triangles are closed up once their mean squared edge is less than this share of cell distance squared
+const CONVERGENT_SHRINK: f32 = 0.6;
+//diverging ones are filled in once it is more than this share
+const DIVERGENT_STRETCH: f32 = 1.15;
+
+//depth trenches are pulled towards, in km
+const TRENCH_DEPTH: f32 = -9.0;
+//fraction of the way to trench depth cells next to a subducting one are pulled each time
+const TRENCH_RATE: f32 = 0.25;
+//fraction of crust above sea level scraped off onto the overriding plate instead of being subducted
+const ACCRETION: f32 = 0.5;
+//how many cells inland from the margin the volcanic arc forms
+const ARC_DISTANCE: usize = 3;
+//km of uplift in the arc for each cell subducted, scaled by how much water the subducted crust carries down
+const ARC_UPLIFT: f32 = 0.2;
+
+//km of uplift along a suture each time a cell is folded into it, falling off across the belt
+pub const SUTURE_UPLIFT: f32 = 0.4;
+//how many cells either side of a suture are raised
+const BELT_WIDTH: usize = 2;
+
+//km of swell a plume holds the crust up by, for every km per million years it builds
+const PLUME_SWELL: f32 = 0.5;
+//radius of the area each plume feeds, in cell distances
+const PLUME_RADIUS: f32 = 1.5;
+
+//plates with more than this share of the surface are big enough to start rifting apart
+const SUPERCONTINENT_SHARE: f32 = 1.0/3.0;
+//average years a plate that big takes to rift apart
+const RIFT_TIME: f32 = 50_000_000.0;
+
+//driving forces on plates, as the speed in radians per year they would give a plate with as many cells as it has boundary edges
+//slab pull from sinking crust at trenches is much stronger than ridge push from spreading ridges
+const SLAB_PULL: f32 = 0.4/PLANET_RADIUS;
+const RIDGE_PUSH: f32 = 0.05/PLANET_RADIUS;
+//resistance to moving for every cell of a plate, continents have deep keels so drag more
+const BASAL_DRAG: f32 = 1.0;
+const KEEL_DRAG: f32 = 1.0;
+//resistance for every edge of a plate where continents are being pushed together, enough to lock plates if the collision is long enough
+const COLLISION_DRAG: f32 = 20.0;
+//plates held below this by collision drag are locked together, and can be sutured into one, same units as plate speed
+const LOCKED_SPEED: f32 = 0.005/PLANET_RADIUS;
+//years for plates to mostly catch up to the speed the forces on them give
+const PLATE_RESPONSE: f32 = 1_000_000.0;
+//fastest a plate can go, in radians per year
+const MAX_PLATE_SPEED: f32 = 0.2/PLANET_RADIUS;
+
+//m of slip faults can lock up before breaking, each fault varying by up to half this either way until it next breaks
+const STRAIN_LIMIT: f32 = 10.0;
+//most m of strain loaded onto a fault at once, well under the weakest fault so each gets the chance to break as it passes its limit
+const LOAD_STEP: f32 = STRAIN_LIMIT*0.25;
+//most loading steps a single call is split into, so long steps stay quick, faults loaded faster than that break at most once a loading step
+const MAX_LOAD_STEPS: u32 = 128;
+//cells along a fault loaded past this share of their own limit can break along with a neighbour that breaks, more likely the more loaded they are
+const RUPTURE_SHARE: f32 = 0.5;
+//chance of a neighbour breaking along for every limits worth of strain it has past that share
+const RUPTURE_SPREAD: f32 = 0.5;
+//m down a fault that breaks in an earthquake, the rest being too hot and soft to stick
+const FAULT_WIDTH: f32 = 30_000.0;
+//shear modulus of crust, in pascals
+const RIGIDITY: f32 = 3.0e10;
+
+//average years between eruptions big enough to log from a single cell, for each kind of volcano
+const ARC_ERUPTION_TIME: f32 = 100_000.0;
+const RIFT_ERUPTION_TIME: f32 = 200_000.0;
+const HOTSPOT_ERUPTION_TIME: f32 = 50_000.0;
+//volcanic explosivity index of the smallest eruption logged, each index up being ten times rarer and ten times bigger
+const SMALLEST_ERUPTION: f32 = 4.0;
+const LARGEST_ERUPTION: f32 = 8.0;
+//km of rock piled on a cell for every km^3 erupted, as if it all landed within a volcanic field of 1000km^2
+const ERUPTION_UPLIFT: f32 = 0.001;
+//optical depth of aerosols added for every km^3 erupted
+const AEROSOL_YIELD: f32 = 0.015;
+//degrees C the planet cools by for every unit of aerosol optical depth, when there is little of it
+const AEROSOL_COOLING: f32 = 3.0;
+//most degrees C aerosols can cool by, however thick they get
+const MAX_VOLCANIC_COOLING: f32 = 10.0;
+//years for volcanic aerosols to mostly settle out of the stratosphere
+const AEROSOL_LIFETIME: f32 = 1.0;
+
+//fraction of its thickness continental crust loses each time it is stretched apart at a rift
+const RIFT_THINNING: f32 = 0.02;
+//years for the surface to get most of the way to floating in balance again, as the mantle flows out of or back under it
+const ISOSTATIC_RESPONSE: f32 = 10_000.0;
+
+//height sediment washed out to sea builds up to before spilling further out, in km
+const SHELF_LEVEL: f32 = -0.2;
+
+//most triangles retriangulated together in one patch, bigger patches can be triangulated better but cost more
+const PATCH_SIZE: usize = 16;
+
+//group of boundary triangles retriangulated together, always a single piece with no holes so its outline is one polygon
+struct Patch{
+    triangles: Vec<u32>,
+    //cells inside the patch being subducted or folded into a suture, which are left out of the new triangles
+    consumed: Vec<usize>,
+}
+
+//degrees C volcanic aerosols of a given optical depth cool the planet by, levelling off as thick aerosols mostly block what sunlight they can
+pub fn volcanic_cooling(aerosols:f32)->f32{
+    MAX_VOLCANIC_COOLING*(1.0-(-aerosols*AEROSOL_COOLING/MAX_VOLCANIC_COOLING).exp())
+}
 
 impl super::surface::Surface{
     //handles tempereture updating
-    pub fn temperature(&mut self,years:f32,sim_info: &SimInfo){
+    pub fn temperature(&mut self,_years:f32,sim_info: &SimInfo){
         //latitude that gets maximum sunlight from the sun
         let sun_max = glm::dot(&sim_info.to_sun, &sim_info.axis);
+        let cooling = volcanic_cooling(sim_info.aerosols);
         //updates temp for each
         for cell in self.cells.iter_mut(){
             //amount of light recieved as percentage compared to ideal
             //calculates latitude and gets its distance from the ideal/max 
             let light_angle_multiplier = glm::max2_scalar(1.0-f32::abs(sun_max- glm::dot(&cell.position,&sim_info.axis)), 0.0);
             //multiplies ideal temp by angle, then takes lapse rate*height away if above sea level
-            cell.contents.temperature = ((sim_info.base_temp*light_angle_multiplier)-(glm::max2_scalar(cell.contents.height,0.0)*sim_info.lapse_rate));
+            //volcanic aerosols shade everywhere alike
+            cell.contents.temperature = (sim_info.base_temp*light_angle_multiplier)-(glm::max2_scalar(cell.contents.height,0.0)*sim_info.lapse_rate)-cooling;
         }
     }
 
     //handles the teconics on the planets surface
     pub fn tectonics(&mut self,years:f32,sim_info: &mut SimInfo){        
+        self.classify_boundaries();
+        self.plate_motion(years);
         //for every cell with plate info, move according to plate
         for cell in self.cells.iter_mut().filter(|c|c.plate.is_some()){
             //plate cell belongs too
@@ -38,6 +146,11 @@ impl super::surface::Surface{
         for plate in self.plates.iter_mut(){
             plate.translation = glm::rotate(&plate.translation, plate.speed*years,  &plate.axis);
         }
+        self.time += years as f64;
+        self.age_crust(years);
+        self.hotspot_volcanism(years);
+        self.earthquakes(years);
+        self.eruptions(years, sim_info);
 
         //update counter, check if exceeds interval
         if sim_info.triangulation_interval > self.since_triangulation{
@@ -47,81 +160,808 @@ impl super::surface::Surface{
         self.since_triangulation = 0.0;
         
         //different types of boundary triangles
-        let mut divergent:Vec<u32> = Vec::with_capacity(self.triangles.len()/3);
-        let mut convergent:Vec<u32> = Vec::with_capacity(self.triangles.len()/3);
-        let mut transform:Vec<u32> = Vec::with_capacity(self.triangles.len()/3);
-
-        //filter boundary triangles out of mesh, record them
-        self.triangles = self.triangles.chunks(3)
-            .filter(|t| {
-                //filter out triangles that contain cells in more than one plate, store seperately
-                let plate = self.cells[t[0] as usize].plate;
-                if t.iter().all(|i| self.cells[*i as usize].plate==plate){
-                    true
-                }else{
-                    //evaluate boundry type of triangle and store appropriately
-                    //use perimeter^2 of triangle to determine boundry type
-                    let sqr_perim:f32 = t.iter().zip(t.iter().skip(1))
-                    .map(|e| (self.cells[*e.0 as usize].position-self.cells[*e.1 as usize].position).magnitude_squared())
-                    .sum();
-
-                    if sqr_perim/3.0 < self.cell_distance*self.cell_distance*0.5{
-                        t.iter().for_each(|x| convergent.push(*x));
-                    }
-                    else if sqr_perim/3.0 > self.cell_distance*self.cell_distance*1.25{
-                        t.iter().for_each(|x| divergent.push(*x));
-                    }
-                    else{
-                        t.iter().for_each(|x| transform.push(*x));
+        let mut divergent:Vec<u32> = Vec::new();
+        let mut convergent:Vec<u32> = Vec::new();
+        let mut transform:Vec<u32> = Vec::new();
+
+        //record boundary triangles, those that contain cells in more than one plate
+        //each takes the type of its boundary edges, converging taking priority as it is what the mesh has to make room for
+        let kinds:HashMap<(usize,usize),BoundaryType> = self.boundaries.iter()
+            .map(|edge| (edge.cells,edge.kind))
+            .collect();
+        for t in self.mesh.faces(){
+            let edge_kinds:Vec<BoundaryType> = (0..3)
+                .map(|i| (t[i] as usize,t[(i+1)%3] as usize))
+                .filter_map(|(a,b)| kinds.get(&(a.min(b),a.max(b))).copied())
+                .collect();
+            if edge_kinds.contains(&BoundaryType::Convergent){
+                convergent.extend_from_slice(&t);
+            }else if edge_kinds.contains(&BoundaryType::Divergent){
+                divergent.extend_from_slice(&t);
+            }else if !edge_kinds.is_empty(){
+                transform.extend_from_slice(&t);
+            }
+        }
+        //mean squared edge of a triangle, for how far it has been squashed or stretched
+        let spread = |tri:&[u32]| (0..3)
+            .map(|i| (self.cells[tri[i] as usize].position-self.cells[tri[(i+1)%3] as usize].position).magnitude_squared())
+            .sum::<f32>()/3.0;
+        let cell_area = self.cell_distance*self.cell_distance;
+
+        //act on boundary triangles based what they are catigorized as
+        //remove most dense cell in converging, the denser crust is the one that sinks
+        //if all are continental neither sinks, but the cell is still removed as its crust is folded into its neighbours
+        let consumed:BTreeSet<usize> = convergent.chunks(3)
+            .filter(|tri| spread(tri) < cell_area*CONVERGENT_SHRINK)
+            .map(|tri| *tri.iter()
+                .max_by(|a,b| self.crust_density(**a as usize).total_cmp(&self.crust_density(**b as usize))
+                    //lower plate sinks if the same, so it doesnt depend on the order of the triangle
+                    .then(self.cells[**b as usize].plate.cmp(&self.cells[**a as usize].plate)))
+                .unwrap() as usize)
+            .collect();
+        //only gaps wide enough for a cell are filled
+        let stretched:Vec<u32> = divergent.chunks(3)
+            .filter(|tri| spread(tri) > cell_area*DIVERGENT_STRETCH)
+            .flatten()
+            .copied()
+            .collect();
+
+        //turn triangles into polygons
+        let boundary = [convergent.as_slice(),divergent.as_slice(),transform.as_slice()].concat();
+        let patches = self.boundary_patches(&boundary, &consumed);
+
+        //triangulate polygons, recording where the triangles of each patch end up for adding cells to
+        let mut patch_of:HashMap<[u32;3],usize> = HashMap::new();
+        let mut patch_triangles:Vec<Vec<u32>> = Vec::with_capacity(patches.len());
+        //pairs of plates that have folded crust between them
+        let mut sutured:BTreeSet<(usize,usize)> = BTreeSet::new();
+        for (i,patch) in patches.iter().enumerate(){
+            patch_of.extend(patch.triangles.chunks(3).map(|t| (lowest_first([t[0],t[1],t[2]]),i)));
+            //neighbours have to be found before the consumed cells leave the mesh
+            let surrounding:Vec<Vec<usize>> = patch.consumed.iter()
+                .map(|c| self.adjacency().neighbours(*c).collect())
+                .collect();
+            let (triangles,consumed) = match self.retriangulate(patch){
+                Some(triangles)=> (triangles,patch.consumed.clone()),
+                //outline couldnt be triangulated as a whole, so consumed cells are taken out one at a time instead
+                //the rest of the patch keeps its triangles, and is left for delaunay_boundaries to tidy up
+                None=> {
+                    let mut removed = Vec::new();
+                    for cell in patch.consumed.iter(){
+                        let apex = self.adjacency().neighbours(*cell).find(|n| !patch.consumed.contains(n));
+                        if let Some(apex) = apex{
+                            self.remove_cell(*cell, apex);
+                            removed.push(*cell);
+                        }
                     }
-                    false
+                    let kept:Vec<u32> = patch.triangles.chunks(3)
+                        .filter(|t| self.mesh.opposite(t[0],t[1]) == Some(t[2]))
+                        .flatten()
+                        .copied()
+                        .collect();
+                    (kept,removed)
+                },
+            };
+            for (cell,neighbours) in patch.consumed.iter().zip(surrounding.iter()).filter(|(c,_)| consumed.contains(c)){
+                if self.is_collision(*cell, neighbours){
+                    let plate = self.cells[*cell].plate;
+                    sutured.extend(neighbours.iter()
+                        .filter_map(|n| self.cells[*n].plate.zip(plate))
+                        .filter(|(a,b)| a != b)
+                        .map(|(a,b)| (a.min(b),a.max(b))));
+                    self.collide(*cell, neighbours);
+                }else{
+                    self.subduct(*cell, neighbours);
                 }
-            })
-            .flatten()
-            .map(|x| *x)
+            }
+            self.bank.extend(consumed);
+            patch_triangles.push(triangles);
+        }
+
+        //continents being pulled apart are stretched thin, and sink into rift valleys
+        let rifting:BTreeSet<usize> = divergent.iter()
+            .map(|c| *c as usize)
+            .filter(|c| self.cells[*c].crust.is_continental())
             .collect();
+        for cell in rifting{
+            let thickness = self.cells[cell].crust.thickness;
+            self.cells[cell].crust.thickness = thickness*(1.0-RIFT_THINNING);
+        }
 
-        //act on boundary triangles based what they are catigorized as
-        //println!("\nConverging:{:?}\nTransform:{:?}\nDivergent:{:?}",convergent.len(),transform.len(),divergent.len());
-        //remove cells in converging
-        for tri in convergent.chunks(3){
-            //remove most dense cell
-            self.bank.insert(tri[0] as usize);
-            
-        }
-        //records cells already added to prevent duplicates
-        let mut newly_added:HashSet<u32> = HashSet::new();
         //get cells that can be added to mesh
-        let mut bank_cells:Vec<usize> = self.bank.drain().collect();
-        //add new cells according to base mesh
-        for tri in divergent.chunks(3){
+        let mut bank_cells:Vec<usize> = std::mem::take(&mut self.bank).into_iter().collect();
+        //add new cells according to base mesh, in the gaps left by diverging plates
+        for tri in stretched.chunks(3){
             //get index of cell to be added to mesh
-            let cell_to_add = if let Some(cell) = bank_cells.pop(){
-                cell
+            let cell_to_add = if let Some(cell) = bank_cells.last(){
+                *cell
             }else{
                 //no free space for new cell
-                continue;
+                break;
             };
             //find edge with two points of the same plate in tri to be used create new one
-            let shared_plate = tri.iter().zip(tri.iter().skip(1))
-                .find(|edge| self.cells[*edge.0 as usize].plate == self.cells[*edge.1 as usize].plate);
-            if let Some(edge) = shared_plate{
-                //add new cell that corrosponds to the next point in the virtual mesh's 
-                self.add_cell((*edge.0 as usize,*edge.1 as usize), cell_to_add);
-            }else{
-                //no shared plate found
+            let shared_plate = (0..3).map(|i| (tri[i] as usize,tri[(i+1)%3] as usize))
+                .find(|edge| self.cells[edge.0].plate == self.cells[edge.1].plate);
+            //new cell corrosponds to the next point in the virtual mesh
+            let (pos,index) = match shared_plate.and_then(|edge| self.cell_completing(edge)){
+                Some(new)=> new,
+                None=> continue,
+            };
+            //triangles can be missing if a patch was changed around them
+            let patch = match patch_of.get(&lowest_first([tri[0],tri[1],tri[2]])){
+                Some(patch)=> *patch,
+                None=> continue,
+            };
+            //banked cells arent used, so can be overwritten even if it doesnt get added
+            //new crust wells up at the ridge, and sinks again as it spreads away and cools
+            self.cells[cell_to_add] = self.ridge_cell(shared_plate.unwrap(), pos, index);
+            //only added if it falls in the gap, otherwise it would overlap the rest of the mesh
+            if self.insert_cell(cell_to_add, &mut patch_triangles[patch]){
+                bank_cells.pop();
+            }
+        }
+
+        //add remaining unused cells back to bank
+        self.bank = BTreeSet::from_iter(bank_cells);
+
+        //tidy up the triangles left around the boundaries
+        self.delaunay_boundaries();
+        //plates have gained and lost crust
+        self.update_plate_densities();
+
+        //plates locked together become one, and ones grown too big break apart, so continents gather and split up again over time
+        self.suture_plates(sutured);
+        self.rift_plates(sim_info.triangulation_interval);
+        //the mesh and plates have changed under the boundaries
+        self.classify_boundaries();
+    }
+
+    //merges plates that have collided and locked together, the one with fewer cells joining the other
+    fn suture_plates(&mut self,sutured:BTreeSet<(usize,usize)>){
+        let mut pairs:Vec<(usize,usize)> = sutured.into_iter().collect();
+        let mut sizes = self.plate_sizes();
+        while let Some((a,b)) = pairs.pop(){
+            if a == b || self.plates[a].speed > LOCKED_SPEED || self.plates[b].speed > LOCKED_SPEED{
                 continue;
             }
+            let (keep,absorb) = if sizes[a] >= sizes[b] {(a,b)} else {(b,a)};
+            self.merge_plates(keep, absorb);
+            //sizes and pairs still to check have to follow the remapped ids
+            sizes[keep] += sizes[absorb];
+            sizes.remove(absorb);
+            for pair in pairs.iter_mut(){
+                *pair = (merged_id(pair.0, keep, absorb),merged_id(pair.1, keep, absorb));
+            }
         }
+    }
 
-        //turn triangles into polygons
+    //gives each oversized plate a chance of splitting, so it takes RIFT_TIME years on average
+    fn rift_plates(&mut self,years:f32){
+        let live = (self.cells.len()-self.bank.len()) as f32;
+        let mut sizes = self.plate_sizes();
+        for plate in 0..sizes.len(){
+            if sizes[plate] as f32 > live*SUPERCONTINENT_SHARE && self.rng.gen::<f32>() < years/RIFT_TIME{
+                if let Some((_,moved)) = self.split_plate(plate){
+                    sizes[plate] -= moved;
+                    sizes.push(moved);
+                }
+            }
+        }
+    }
 
-        //triangulate polygons
+    //lets the surface rise or sink towards the height its crust would float at in the mantle
+    //thickened crust rises, thinned crust sinks, and anything worn away is partly made up for as the crust under it rebounds
+    pub fn isostasy(&mut self,years:f32){
+        let relax = 1.0-(-years/ISOSTATIC_RESPONSE).exp();
+        for cell in self.cells.iter_mut(){
+            let height = cell.contents.height;
+            cell.set_height(height+(cell.crust.equilibrium_height()-height)*relax);
+        }
+    }
 
-        //add remaining unused cells back to bank
-        self.bank = HashSet::from_iter(bank_cells.into_iter());
+    //rivers cut into the land following the stream power law, faster the more area drains through them and the steeper they are
+    //what they wear away is carried downstream and dropped wherever there is more than the river can carry, in basins with no way out, or out to sea
+    //water always takes the steepest way down, so every cell drains to at most one other and the order can be worked out from height alone
+    pub fn hydraulic_erosion(&mut self,years:f32,sim_info:&SimInfo){
+        let height = |cell:usize| self.cells[cell].contents.height;
+        //highest first, so everything upstream of a cell is done before it
+        let mut order:Vec<usize> = (0..self.cells.len()).filter(|c| !self.bank.contains(c)).collect();
+        order.sort_by(|a,b| height(*b).total_cmp(&height(*a)).then(a.cmp(b)));
+
+        //steepest neighbour downhill of each cell and the slope to it, in m per m
+        let mut receiver:Vec<Option<(usize,f32)>> = vec![None;self.cells.len()];
+        for cell in order.iter().copied(){
+            for n in self.adjacency().neighbours(cell){
+                let distance = great_circle_distance(&self.cells[cell].position, &self.cells[n].position)*PLANET_RADIUS;
+                let slope = (height(cell)-height(n))*1000.0/distance;
+                if slope > receiver[cell].map_or(0.0, |r| r.1){
+                    receiver[cell] = Some((n,slope));
+                }
+            }
+        }
+        //cells worth of area draining through each cell, including its own
+        let mut area:Vec<f32> = vec![1.0;self.cells.len()];
+        for cell in order.iter().copied(){
+            if let Some((down,_)) = receiver[cell]{
+                area[down] += area[cell];
+            }
+        }
+
+        //sediment each cell is passed from upstream, in km spread over one cell
+        let mut sediment:Vec<f32> = vec![0.0;self.cells.len()];
+        let cell_size = self.cell_distance*PLANET_RADIUS;
+        for cell in order{
+            let current = self.cells[cell].contents.height;
+            match receiver[cell]{
+                Some((down,slope)) if current > 0.0=> {
+                    //in km, rivers cant cut below where they flow to or below the sea they run into
+                    let power = sim_info.erodibility*area[cell].sqrt()*cell_size*slope*years/1000.0;
+                    let incision = power.min(current-self.cells[down].contents.height.max(0.0));
+                    let carried = sediment[cell]+incision;
+                    let dropped = (carried-power*sim_info.sediment_capacity).max(0.0);
+                    self.cells[cell].erode(incision);
+                    self.cells[cell].deposit(dropped);
+                    sediment[down] += carried-dropped;
+                },
+                //at sea sediment builds out a shelf, spilling down the slope once it is full
+                Some((down,_))=> {
+                    let dropped = sediment[cell].min((SHELF_LEVEL-current).max(0.0));
+                    self.cells[cell].deposit(dropped);
+                    sediment[down] += sediment[cell]-dropped;
+                },
+                //basins with no way out and the deepest sea floor keep whatever reaches them
+                None=> self.cells[cell].deposit(sediment[cell]),
+            }
+        }
+    }
+
+    //slopes steeper than the talus angle give way, and soil creeps down whatever slopes are left on land, smoothing relief out over time
+    //every edge moves material at once from the heights as they were, so the result doesnt depend on the order cells are visited
+    pub fn thermal_erosion(&mut self,years:f32,sim_info:&SimInfo){
+        let heights:Vec<f32> = self.cells.iter().map(|c| c.contents.height).collect();
+        let mut change:Vec<f32> = vec![0.0;self.cells.len()];
+        let talus = sim_info.talus_angle.to_radians().tan();
+        let adjacency = self.adjacency();
+        let degrees:Vec<usize> = (0..self.cells.len()).map(|cell| adjacency.degree(cell)).collect();
+        for (a,b) in adjacency.edges(){
+            let (high,low) = if heights[a] >= heights[b] {(a,b)} else {(b,a)};
+            let drop = heights[high]-heights[low];
+            //in km, same as height
+            let distance = great_circle_distance(&self.cells[a].position, &self.cells[b].position)*PLANET_RADIUS/1000.0;
+            //each edge only moves its share of half the difference, so a cell cant lose more than would level it with its neighbours
+            let share = 0.5/degrees[a].max(degrees[b]) as f32;
+            //anything above the talus slope slides straight down
+            let excess = (drop-distance*talus).max(0.0);
+            let mut moved = excess*share;
+            //hillslope diffusion, relaxing the rest of the slope towards flat
+            if heights[high] > 0.0{
+                let relax = 1.0-(-sim_info.diffusivity*years/(distance*distance*1_000_000.0)).exp();
+                moved += (drop-excess)*share*relax;
+            }
+            change[high] -= moved;
+            change[low] += moved;
+        }
+        for (cell,change) in self.cells.iter_mut().zip(change){
+            if change < 0.0{
+                cell.erode(-change);
+            }else if change > 0.0{
+                cell.deposit(change);
+            }
+        }
+    }
 
-        //triangulate new boundary triangles, insert into mesh
-        self.triangles.append(&mut transform);
+    //velocity of a cell from the rotation of its plate, in radians per year
+    pub fn cell_velocity(&self,cell:usize)->glm::Vec3{
+        self.cells[cell].plate.map_or(glm::Vec3::zeros(), |p| {
+            let plate = &self.plates[p];
+            glm::cross(&(plate.axis*plate.speed), &self.cells[cell].position)
+        })
+    }
+
+    //how the plates of two neighbouring cells move relative to each other, measured where they meet
+    pub fn boundary_edge(&self,a:usize,b:usize)->BoundaryEdge{
+        let rotation = |cell:usize| self.cells[cell].plate.map_or(glm::Vec3::zeros(), |p| self.plates[p].axis*self.plates[p].speed);
+        let (pa,pb) = (&self.cells[a].position,&self.cells[b].position);
+        let middle = (pa+pb).normalize();
+        let relative = glm::cross(&(rotation(a)-rotation(b)), &middle);
+        //normal to the boundary, along the surface from a towards b
+        let normal = pb-pa;
+        let normal = (normal-middle*glm::dot(&normal,&middle)).normalize();
+        let closing = glm::dot(&relative,&normal);
+        BoundaryEdge::new((a,b), closing, (relative-normal*closing).magnitude())
+    }
+
+    //finds and classifies every edge between cells on different plates
+    pub fn classify_boundaries(&mut self){
+        self.boundaries = self.adjacency().edges()
+            .filter(|(a,b)| match (self.cells[*a].plate,self.cells[*b].plate){
+                (Some(pa),Some(pb))=> pa != pb,
+                _=> false,
+            })
+            .map(|(a,b)| self.boundary_edge(a,b))
+            .collect();
+    }
+
+    //turns the forces acting on each plate into the rotation it moves with, so euler poles and speeds follow how plates are arranged
+    //plates are pulled towards trenches they sink into and pushed away from ridges, and held back by the mantle under them and by colliding continents
+    pub fn plate_motion(&mut self,years:f32){
+        let mut torque:Vec<glm::Vec3> = vec![glm::Vec3::zeros();self.plates.len()];
+        let mut drag:Vec<f32> = vec![0.0;self.plates.len()];
+        //edge forces and cell drag scaled by their length and area, so speeds dont change with how finely the planet is divided
+        let length = self.cell_distance;
+        for (i,cell) in self.cells.iter().enumerate(){
+            if let (Some(p),false) = (cell.plate,self.bank.contains(&i)){
+                drag[p] += (BASAL_DRAG+if cell.crust.is_continental() {KEEL_DRAG} else {0.0})*length*length;
+            }
+        }
+        for edge in self.boundaries.iter(){
+            let (a,b) = edge.cells;
+            let (ca,cb) = (&self.cells[a],&self.cells[b]);
+            let (pa,pb) = match (ca.plate,cb.plate){
+                (Some(pa),Some(pb))=> (pa,pb),
+                _=> continue,
+            };
+            //direction along the surface from one cell towards the other
+            let towards = |from:&glm::Vec3,to:&glm::Vec3| (to-from*glm::dot(from,to)).normalize();
+            let (ab,ba) = (towards(&ca.position,&cb.position),towards(&cb.position,&ca.position));
+            match edge.kind{
+                BoundaryType::Convergent=> if ca.crust.is_continental() && cb.crust.is_continental(){
+                    drag[pa] += COLLISION_DRAG*length;
+                    drag[pb] += COLLISION_DRAG*length;
+                }else{
+                    //denser crust sinks and drags its plate after it, harder the older and colder it is
+                    let (sinking,direction,p) = if ca.crust.density >= cb.crust.density {(ca,ab,pa)} else {(cb,ba,pb)};
+                    let weight = 0.5+0.5*(sinking.crust.age/crust::SUBSIDENCE_AGE).min(1.0);
+                    torque[p] += glm::cross(&sinking.position, &(direction*SLAB_PULL*weight*length));
+                },
+                BoundaryType::Divergent=> {
+                    torque[pa] -= glm::cross(&ca.position, &(ab*RIDGE_PUSH*length));
+                    torque[pb] -= glm::cross(&cb.position, &(ba*RIDGE_PUSH*length));
+                },
+                //plates sliding past each other neither push nor pull
+                BoundaryType::Transform=> (),
+            }
+        }
+        let relax = 1.0-(-years/PLATE_RESPONSE).exp();
+        for ((plate,torque),drag) in self.plates.iter_mut().zip(torque).zip(drag){
+            if drag == 0.0{
+                continue;
+            }
+            let rotation = plate.axis*plate.speed;
+            let rotation = rotation+(torque/drag-rotation)*relax;
+            let speed = rotation.magnitude();
+            if speed > 0.0{
+                plate.axis = rotation/speed;
+            }
+            plate.speed = speed.min(MAX_PLATE_SPEED);
+        }
+    }
+
+    //builds volcanoes over each mantle plume, on a swell of crust held up by the heat
+    //as plates carry them off the plume they stop growing and sink as they cool, leaving a chain of islands and seamounts
+    pub fn hotspot_volcanism(&mut self,years:f32){
+        let radius = self.cell_distance*PLUME_RADIUS;
+        for h in 0..self.hotspots.len(){
+            let hotspot = &self.hotspots[h];
+            let distance = |c:usize| (self.cells[c].position-hotspot.position).magnitude();
+            //cells over the plume, spreading out from the one right above it
+            let mut over:Vec<usize> = self.nearest_cell(&hotspot.position)
+                .filter(|c| distance(*c) < radius)
+                .into_iter()
+                .collect();
+            let mut reached:HashSet<usize> = over.iter().copied().collect();
+            let mut i = 0;
+            while i < over.len(){
+                for n in self.adjacency().neighbours(over[i]){
+                    if distance(n) < radius && reached.insert(n){
+                        over.push(n);
+                    }
+                }
+                i += 1;
+            }
+            let strengths:Vec<(usize,f32)> = over.into_iter()
+                .map(|c| (c,hotspot.strength*(1.0-distance(c)/radius)))
+                .collect();
+            for (cell,strength) in strengths{
+                let cell = &mut self.cells[cell];
+                cell.uplift(strength*years/1_000_000.0);
+                cell.crust.swell = cell.crust.swell.max(strength*PLUME_SWELL);
+            }
+        }
+    }
+
+    //builds strain where plates grind together or past each other, breaking faults that cant hold any more as earthquakes
+    //a break spreads along the boundary to neighbours near breaking themselves, so longer ruptures make bigger earthquakes
+    //strain is loaded a bit at a time, so faults build up to breaking over several steps, or break several times in a long one
+    pub fn earthquakes(&mut self,years:f32){
+        //rate each cell is loaded at, in m per year, from the fastest fault it is on
+        //plates pulling apart stretch the crust rather than locking it, so only make small earthquakes not worth tracking
+        let mut loading:BTreeMap<usize,f32> = BTreeMap::new();
+        for edge in self.boundaries.iter().filter(|e| e.kind != BoundaryType::Divergent){
+            let rate = (edge.closing*edge.closing+edge.shear*edge.shear).sqrt()*PLANET_RADIUS;
+            for cell in [edge.cells.0,edge.cells.1]{
+                let load = loading.entry(cell).or_insert(0.0);
+                *load = load.max(rate);
+            }
+        }
+        let fastest = loading.values().fold(0.0, |a:f32,b| a.max(*b));
+        let steps = ((fastest*years/LOAD_STEP).ceil() as u32).clamp(1, MAX_LOAD_STEPS);
+        let fault_length = self.cell_distance*PLANET_RADIUS;
+        //faults can break many times a step, so what they are next to is only found once
+        let faults:BTreeMap<usize,Vec<usize>> = loading.keys()
+            .map(|c| (*c,self.adjacency().neighbours(*c).collect()))
+            .collect();
+        for step in 0..steps{
+            //time is already at the end of the whole step
+            let time = self.time-(years*(steps-step-1) as f32/steps as f32) as f64;
+            for (cell,rate) in loading.iter(){
+                let crust = &mut self.cells[*cell].crust;
+                crust.strain += rate*years/steps as f32;
+                if crust.rupture == 0.0{
+                    crust.rupture = STRAIN_LIMIT*self.rng.gen_range(0.5..1.5);
+                }
+            }
+            for cell in loading.keys().copied(){
+                if self.cells[cell].crust.strain >= self.cells[cell].crust.rupture{
+                    self.rupture(cell, &faults, fault_length, time);
+                }
+            }
+        }
+    }
+
+    //breaks a fault in an earthquake, spreading along the boundary from a cell past its limit
+    //faults are the loaded cells and their neighbours
+    fn rupture(&mut self,cell:usize,faults:&BTreeMap<usize,Vec<usize>>,fault_length:f32,time:f64){
+        let mut rupture:Vec<usize> = vec![cell];
+        let mut open:Vec<usize> = vec![cell];
+        while let Some(current) = open.pop(){
+            for next in faults[&current].iter().copied(){
+                if !faults.contains_key(&next) || rupture.contains(&next){
+                    continue;
+                }
+                let crust = &self.cells[next].crust;
+                let chance = (crust.strain/crust.rupture-RUPTURE_SHARE)*RUPTURE_SPREAD;
+                if self.rng.gen::<f32>() < chance{
+                    rupture.push(next);
+                    open.push(next);
+                }
+            }
+        }
+        //seismic moment from the area that slipped and how far, turned into moment magnitude
+        let slip = rupture.iter().map(|c| self.cells[*c].crust.strain).sum::<f32>()/rupture.len() as f32;
+        let moment = RIGIDITY*fault_length*rupture.len() as f32*FAULT_WIDTH*slip;
+        let magnitude = (moment.log10()-9.1)*2.0/3.0;
+        let mut centre = glm::Vec3::zeros();
+        for c in rupture.iter(){
+            centre += self.cells[*c].position;
+            self.cells[*c].crust.strain = 0.0;
+            self.cells[*c].crust.rupture = STRAIN_LIMIT*self.rng.gen_range(0.5..1.5);
+            for shaken in std::iter::once(*c).chain(faults[c].iter().copied()){
+                let crust = &mut self.cells[shaken].crust;
+                crust.hazard = crust.hazard.max(magnitude);
+            }
+        }
+        self.events.push(Event { kind: EventKind::Earthquake, position: centre.normalize(), magnitude, time });
+    }
+
+    //cells volcanoes can erupt from, and what feeds them
+    //arcs form a few cells in from a trench on the overriding plate, rifts along diverging boundaries, and hotspots over each plume
+    pub fn volcanoes(&self)->BTreeMap<usize,VolcanoKind>{
+        let mut volcanoes:BTreeMap<usize,VolcanoKind> = BTreeMap::new();
+        for hotspot in self.hotspots.iter(){
+            if let Some(cell) = self.nearest_cell(&hotspot.position){
+                if (self.cells[cell].position-hotspot.position).magnitude() < self.cell_distance*PLUME_RADIUS{
+                    volcanoes.insert(cell, VolcanoKind::Hotspot);
+                }
+            }
+        }
+        for edge in self.boundaries.iter(){
+            let (a,b) = edge.cells;
+            let (ca,cb) = (&self.cells[a].crust,&self.cells[b].crust);
+            match edge.kind{
+                BoundaryType::Convergent if !(ca.is_continental() && cb.is_continental())=> {
+                    //same as plate motion, the denser side sinks
+                    let overriding = if ca.density >= cb.density {b} else {a};
+                    let plate = self.cells[overriding].plate;
+                    let mut reached:HashSet<usize> = HashSet::from([overriding]);
+                    let mut ring:Vec<usize> = vec![overriding];
+                    for _ in 1..ARC_DISTANCE{
+                        ring = ring.iter()
+                            .flat_map(|c| self.adjacency().neighbours(*c))
+                            .filter(|n| self.cells[*n].plate == plate && reached.insert(*n))
+                            .collect();
+                    }
+                    for cell in ring{
+                        volcanoes.entry(cell).or_insert(VolcanoKind::Arc);
+                    }
+                },
+                BoundaryType::Divergent=> for cell in [a,b]{
+                    volcanoes.entry(cell).or_insert(VolcanoKind::Rift);
+                },
+                _=> (),
+            }
+        }
+        volcanoes
+    }
+
+    //erupts volcanoes at random, piling rock up around them and throwing aerosols up that cool the planet for a few years
+    //aerosols settle on the same clock eruptions happen on, so however many steps a frame runs the level only depends on the eruptions before it
+    pub fn eruptions(&mut self,years:f32,sim_info:&mut SimInfo){
+        //aerosols from an eruption somewhere in a step long compared to their lifetime are only left for a small part of it
+        //so each adds what it leaves on average over the step
+        let settled = (-years/AEROSOL_LIFETIME).exp();
+        let left = if years > 0.0 {(1.0-settled)*AEROSOL_LIFETIME/years} else {1.0};
+        sim_info.aerosols *= settled;
+        for (cell,kind) in self.volcanoes(){
+            let recurrence = match kind{
+                VolcanoKind::Arc=> ARC_ERUPTION_TIME,
+                VolcanoKind::Rift=> RIFT_ERUPTION_TIME,
+                VolcanoKind::Hotspot=> HOTSPOT_ERUPTION_TIME,
+            };
+            if self.rng.gen::<f32>() >= years/recurrence{
+                continue;
+            }
+            //each index up is ten times rarer, 1-x so it is never 0
+            let explosivity = (SMALLEST_ERUPTION-(1.0-self.rng.gen::<f32>()).log10()).min(LARGEST_ERUPTION);
+            //km^3 of rock, an index 5 eruption being about 1km^3
+            let volume = 10.0f32.powf(explosivity-5.0);
+            let erupting = &mut self.cells[cell];
+            erupting.uplift(volume*ERUPTION_UPLIFT);
+            erupting.crust.eruption = erupting.crust.eruption.max(explosivity);
+            sim_info.aerosols += volume*AEROSOL_YIELD*left;
+            self.events.push(Event { kind: EventKind::Eruption(kind), position: erupting.position, magnitude: explosivity, time: self.time });
+        }
+    }
+
+    //ages all crust, with oceanic crust sinking as it cools away from the ridge it formed at
+    //only the change in depth is applied, so trenches and arcs on the sea floor are kept
+    pub fn age_crust(&mut self,years:f32){
+        for cell in self.cells.iter_mut(){
+            let sunk = cell.crust.age_by(years);
+            if sunk != 0.0{
+                cell.set_height(cell.contents.height-sunk);
+            }
+        }
+    }
+
+    //flips edges around plate boundaries until the triangles there are delaunay, with no cell inside another triangles circumcircle
+    //keeps triangles as close to equilateral as the cells allow, returns amount of edges flipped
+    pub fn delaunay_boundaries(&mut self)->usize{
+        //cells on a boundary and their neighbours, as the triangles around them are the ones plate motion distorts
+        let mut zone:BTreeSet<u32> = BTreeSet::new();
+        for (a,b) in self.adjacency().edges(){
+            if self.cells[a].plate != self.cells[b].plate{
+                for cell in [a,b]{
+                    zone.insert(cell as u32);
+                    zone.extend(self.adjacency().neighbours(cell).map(|n| n as u32));
+                }
+            }
+        }
+        let mut stack:Vec<(u32,u32)> = self.adjacency().edges()
+            .map(|(a,b)| (a as u32,b as u32))
+            .filter(|(a,b)| zone.contains(a) && zone.contains(b))
+            .collect();
+        //flipping always improves the triangulation so it will end, but rounding could make it go back and forth
+        let limit = stack.len()*8;
+        let mut flips = 0;
+        while let Some((a,b)) = stack.pop(){
+            if flips >= limit{
+                break;
+            }
+            if !self.should_flip(a,b){
+                continue;
+            }
+            if let Some(change) = self.mesh.flip_edge(a,b){
+                flips += 1;
+                //edges around the flipped one may no longer be delaunay
+                stack.extend(change.added.chunks(3)
+                    .flat_map(|t| (0..3).map(move |i| (t[i],t[(i+1)%3])))
+                    .filter(|(a,b)| zone.contains(a) && zone.contains(b)));
+            }
+        }
+        flips
+    }
+
+    //whether an edge should be flipped to make its two triangles delaunay
+    fn should_flip(&self,a:u32,b:u32)->bool{
+        //comparisons are close to 1 for nearby cells, so anything closer than this is treated as on the circle
+        const EPSILON:f32 = 1e-6;
+        let (c,d) = match (self.mesh.opposite(a,b),self.mesh.opposite(b,a)){
+            (Some(c),Some(d)) if c != d=> (c,d),
+            _=> return false,
+        };
+        let points:Vec<glm::Vec3> = [a,b,c,d].iter().map(|i| self.cells[*i as usize].position).collect();
+        //center of the circumcircle on the sphere, d is inside it if closer to it than the triangles own corners are
+        let center = circumcenter(&points, vec![0,1,2]).normalize();
+        if glm::dot(&points[3],&center) <= glm::dot(&points[0],&center)+EPSILON{
+            return false;
+        }
+        //new triangles must be wound the same as the old ones, otherwise the quad isnt convex and flipping would fold the mesh
+        let flat = stereographic_project(&points, (points[0]+points[1]+points[2]+points[3]).normalize());
+        let winding = tri_orientation(flat[0], flat[1], flat[2]);
+        winding != Orientation::Collinear &&
+            tri_orientation(flat[2], flat[0], flat[3]) == winding &&
+            tri_orientation(flat[3], flat[1], flat[2]) == winding
+    }
+
+    //density of a cells crust, cells without a plate dont sink
+    fn crust_density(&self,cell:usize)->f32{
+        match self.cells[cell].plate{
+            Some(_)=> self.cells[cell].crust.density,
+            None=> f32::MIN,
+        }
+    }
+
+    //effects of a cell sinking beneath the plate next to it, should be called once it has left the mesh
+    //neighbours on its own plate are dragged down into a trench, the overriding plate gets whatever crust is scraped off at its margin
+    //and a volcanic arc further inland fed by the water carried down
+    pub fn subduct(&mut self,cell:usize,neighbours:&[usize]){
+        let plate = self.cells[cell].plate;
+        if let Some(p) = plate{
+            self.plates[p].subducted += 1.0;
+        }
+        let (trench,margin):(Vec<usize>,Vec<usize>) = neighbours.iter().partition(|n| self.cells[**n].plate == plate);
+        for n in trench{
+            let height = self.cells[n].contents.height;
+            self.cells[n].set_height(height+(TRENCH_DEPTH-height).min(0.0)*TRENCH_RATE);
+        }
+        if margin.is_empty(){
+            return;
+        }
+
+        //buoyant crust doesnt sink as easily, part of it stays on the overriding plate
+        let scraped = self.cells[cell].contents.height.max(0.0)*ACCRETION/margin.len() as f32;
+        for n in margin.iter(){
+            self.cells[*n].uplift(scraped);
+        }
+
+        //arc is the cells of the overriding plate a few steps in from the margin
+        let overriding = self.cells[margin[0]].plate;
+        let mut reached:HashSet<usize> = margin.iter().copied().collect();
+        reached.insert(cell);
+        let mut ring = margin;
+        let uplift = ARC_UPLIFT*(0.5+self.cells[cell].contents.water);
+        for distance in 2..=ARC_DISTANCE{
+            ring = ring.iter()
+                .flat_map(|c| self.adjacency().neighbours(*c))
+                .filter(|n| self.cells[*n].plate == overriding && reached.insert(*n))
+                .collect();
+            ring.sort();
+            //strongest furthest in, where the sinking plate is deep enough to melt
+            for n in ring.iter(){
+                self.cells[*n].uplift(uplift*distance as f32/ARC_DISTANCE as f32);
+            }
+        }
+    }
+
+    //whether a cell leaving the mesh is continental crust against other continental crust, so folds into a suture rather than sinking
+    fn is_collision(&self,cell:usize,neighbours:&[usize])->bool{
+        let continental = |c:usize| self.cells[c].crust.is_continental();
+        let plate = self.cells[cell].plate;
+        continental(cell) && neighbours.iter()
+            .filter(|n| self.cells[**n].plate != plate)
+            .all(|n| continental(*n))
+    }
+
+    //effects of continental plates colliding, as a cell between them is folded into the suture, should be called once it has left the mesh
+    //its crust thickens its neighbours rather than being lost, and the belt either side is pushed up
+    //the plates are slowed by the drag of the collision in plate_motion, not here
+    pub fn collide(&mut self,cell:usize,neighbours:&[usize]){
+        if neighbours.is_empty(){
+            return;
+        }
+        //crust of the folded cell piles onto the suture
+        let thickening = self.cells[cell].contents.height.max(0.0)/neighbours.len() as f32;
+        let crust = self.cells[cell].crust;
+        for n in neighbours{
+            self.cells[*n].crust.fold(&crust, 1.0/neighbours.len() as f32);
+        }
+        let mut reached:HashSet<usize> = neighbours.iter().copied().collect();
+        reached.insert(cell);
+        let mut ring:Vec<usize> = neighbours.to_vec();
+        for distance in 0..BELT_WIDTH{
+            let uplift = SUTURE_UPLIFT*(1.0-distance as f32/BELT_WIDTH as f32);
+            for n in ring.iter(){
+                //folded crust has already been added to the suture, so only the rest of the belt needs thickening
+                let extra = if distance == 0 {thickening} else {0.0};
+                let height = self.cells[*n].contents.height;
+                self.cells[*n].set_height(height+extra);
+                self.cells[*n].uplift(uplift);
+            }
+            //belt spreads across both plates
+            ring = ring.iter()
+                .flat_map(|c| self.adjacency().neighbours(*c))
+                .filter(|n| reached.insert(*n))
+                .collect();
+            ring.sort();
+        }
+    }
+
+    //groups boundary triangles into patches to be retriangulated
+    //each cell being consumed gets the patch of triangles around it, the rest are grown outwards from single triangles
+    fn boundary_patches(&self,boundary:&[u32],consumed:&BTreeSet<usize>)->Vec<Patch>{
+        let in_boundary:HashSet<[u32;3]> = boundary.chunks(3)
+            .map(|t| lowest_first([t[0],t[1],t[2]]))
+            .collect();
+        let mut claimed:HashSet<[u32;3]> = HashSet::with_capacity(in_boundary.len());
+        let mut patches:Vec<Patch> = Vec::new();
+
+        for cell in consumed.iter(){
+            let around:Vec<[u32;3]> = self.mesh.vertex_faces(*cell as u32).map(lowest_first).collect();
+            let triangles:Vec<u32> = around.iter().flatten().copied().collect();
+            //cell must be surrounded, and not already in another patch
+            if around.is_empty() || around.iter().any(|t| claimed.contains(t)) ||
+                triangles_to_polygon(&triangles).is_none_or(|p| p.contains(cell)){
+                continue;
+            }
+            claimed.extend(around);
+            patches.push(Patch { triangles, consumed: vec![*cell] });
+        }
+
+        for tri in boundary.chunks(3).map(|t| lowest_first([t[0],t[1],t[2]])){
+            if !claimed.insert(tri){
+                continue;
+            }
+            let mut triangles = tri.to_vec();
+            //go through triangles in the patch, adding unclaimed boundary neighbours as long as the patch has no holes
+            let mut i = 0;
+            while i < triangles.len()/3 && triangles.len()/3 < PATCH_SIZE{
+                let t = [triangles[i*3],triangles[i*3+1],triangles[i*3+2]];
+                for k in 0..3{
+                    let neighbour = match self.mesh.find_edge(t[(k+1)%3], t[k]){
+                        Some(edge)=> lowest_first(self.mesh.face(edge)),
+                        None=> continue,
+                    };
+                    if triangles.len()/3 >= PATCH_SIZE || !in_boundary.contains(&neighbour) || claimed.contains(&neighbour){
+                        continue;
+                    }
+                    let grown = [triangles.as_slice(),&neighbour].concat();
+                    if triangles_to_polygon(&grown).is_some(){
+                        triangles = grown;
+                        claimed.insert(neighbour);
+                    }
+                }
+                i += 1;
+            }
+            patches.push(Patch { triangles, consumed: Vec::new() });
+        }
+        patches
+    }
+
+    //replaces the triangles of a patch with a new triangulation of its outline, putting back any cells that were inside it
+    //cells being consumed are left out, returns the new triangles, or none if the patch was left alone as it couldnt be triangulated
+    fn retriangulate(&mut self,patch:&Patch)->Option<Vec<u32>>{
+        let polygon = triangles_to_polygon(&patch.triangles)?;
+        let mut triangles = self.triangulate_outline(&polygon)?;
+
+        //new edges cant already join cells outside of the patch, otherwise they would be shared by too many triangles
+        let in_patch:HashSet<[u32;3]> = patch.triangles.chunks(3).map(|t| lowest_first([t[0],t[1],t[2]])).collect();
+        let outside = |a:u32,b:u32| self.mesh.find_edge(a,b)
+            .is_some_and(|e| !in_patch.contains(&lowest_first(self.mesh.face(e))));
+        if triangles.chunks(3).any(|t| (0..3).any(|i| outside(t[i],t[(i+1)%3]))){
+            return None;
+        }
+
+        //cells inside the patch that stay need to land inside the new triangles, otherwise theyd overlap something
+        let mut interior:Vec<usize> = Vec::new();
+        for cell in patch.triangles.iter().map(|c| *c as usize){
+            if !polygon.contains(&cell) && !patch.consumed.contains(&cell) && !interior.contains(&cell){
+                interior.push(cell);
+            }
+        }
+        let covered = |cell:&usize| triangles.chunks(3).any(|t| {
+            let corners:Vec<glm::Vec3> = t.iter().map(|i| self.cells[*i as usize].position).collect();
+            in_spherical_triangle(&corners, &self.cells[*cell].position)
+        });
+        if !interior.iter().all(covered){
+            return None;
+        }
+
+        self.mesh.remove_faces(&patch.triangles);
+        self.mesh.add_faces(&triangles);
+        for cell in interior{
+            self.insert_cell(cell, &mut triangles);
+        }
+        Some(triangles)
     }
-}
\ No newline at end of file
+}
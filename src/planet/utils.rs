@@ -1,11 +1,8 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 //external crates
 use nalgebra_glm as glm;
-use noise::{NoiseFn, Perlin, Seedable};
-
-//use internal crates
-use crate::planet::surface::Cell;
+use noise::{NoiseFn, Perlin};
 
 #[derive(Debug)]
 #[derive(PartialEq)]
@@ -41,9 +38,11 @@ pub fn stereographic(point: &glm::Vec3)->glm::Vec2{
 }
 
 //takes a set of points, returns their steriographic projection, a normalized "pole" must be defined as a focus point of the projection
-pub fn stereographic_project(points: &Vec<glm::Vec3>, pole:glm::Vec3)->Vec<glm::Vec2>{
+pub fn stereographic_project(points: &[glm::Vec3], pole:glm::Vec3)->Vec<glm::Vec2>{
     //create rotation matrix used to center pole in projection
-    let cross = glm::cross(&pole,&glm::Vec3::y()).normalize();
+    //if the pole is already on the y axis any perpendicular axis will do
+    let cross = glm::cross(&pole,&glm::Vec3::y());
+    let cross = if cross.magnitude_squared() > f32::EPSILON {cross.normalize()} else {glm::Vec3::x()};
     let angle = glm::Vec3::y().angle(&pole);
     let rotation_mat = glm::rotation(angle, &cross);
 
@@ -57,7 +56,7 @@ pub fn stereographic_project(points: &Vec<glm::Vec3>, pole:glm::Vec3)->Vec<glm::
 }
 
 //get connections of every cell
-pub fn indices_to_connections(indices: &Vec<u32>)->Vec<Vec<usize>>{
+pub fn indices_to_connections(indices: &[u32])->Vec<Vec<usize>>{
     //iterate through indices, for every index, store other two in triangle
     let mut connections:Vec::<Vec<usize>> = vec![Vec::with_capacity(6);indices.len()/3];
     //for each triangle
@@ -73,7 +72,7 @@ pub fn indices_to_connections(indices: &Vec<u32>)->Vec<Vec<usize>>{
 }
 
 //gets all edges in the mesh
-pub fn indices_to_edges(indices: &Vec<u32>)->Vec<(usize,usize)>{
+pub fn indices_to_edges(indices: &[u32])->Vec<(usize,usize)>{
     //iterate through indices, for every index, store other two in triangle
     let mut edges:Vec::<(usize,usize)> = Vec::with_capacity(indices.len());
     //for each triangle
@@ -94,7 +93,7 @@ pub fn indices_to_edges(indices: &Vec<u32>)->Vec<(usize,usize)>{
 }
 
 //does the same as the above, but return directed edges, as a result returns double the amount, each edge is ordered the same way as its triangle
-pub fn indices_to_directed_edges(indices: &Vec<u32>)->Vec<(usize,usize)>{
+pub fn indices_to_directed_edges(indices: &[u32])->Vec<(usize,usize)>{
     //iterate through indices, for every index, store other two in triangle
     let mut edges:Vec::<(usize,usize)> = Vec::with_capacity(indices.len());
     indices.chunks(3)//for each triangle
@@ -109,7 +108,7 @@ pub fn indices_to_directed_edges(indices: &Vec<u32>)->Vec<(usize,usize)>{
 }
 
 //gets circumcenter of triangle
-pub fn circumcenter(points: &Vec<glm::Vec3>, tri: Vec<u32>)->glm::Vec3{
+pub fn circumcenter(points: &[glm::Vec3], tri: Vec<u32>)->glm::Vec3{
     //vectors pointing along triangle edges, and their cross product, for calculation
     let atoc = points[tri[2] as usize]-points[tri[0] as usize];
     let atob = points[tri[1] as usize]-points[tri[0] as usize];
@@ -148,10 +147,101 @@ pub fn do_edges_intersect(a:(glm::Vec2,glm::Vec2),b:(glm::Vec2,glm::Vec2))->bool
     (tri_orientation(b.0, b.1, a.1) != tri_orientation(b.0, b.1, a.0))
 }
 
-//takes triangles, turns them into a polygon describing the external edges of the triangles
-//pub fn triangles_to_polygon(tris:Vec<u32>)->Vec<usize>{
+//rotates a triangle to start from its lowest index, keeping its winding
+//so the same triangle always looks the same whichever point it was listed from
+pub fn lowest_first(tri:[u32;3])->[u32;3]{
+    let start = (0..3).min_by_key(|i| tri[*i]).unwrap();
+    [tri[start],tri[(start+1)%3],tri[(start+2)%3]]
+}
 
-//}
+//takes triangles, turns them into a polygon describing the external edges of the triangles, wound the same as them
+//none if the outline isnt a single loop, e.g. if the triangles have holes, touch at a corner, or cover the whole planet
+pub fn triangles_to_polygon(tris:&[u32])->Option<Vec<usize>>{
+    let edges = indices_to_directed_edges(tris);
+    let unique:HashSet<(usize,usize)> = edges.iter().copied().collect();
+    //the same edge in the same direction twice means triangles overlap
+    if unique.len() != edges.len(){
+        return None;
+    }
+    //external edges are the ones without a flipped variant from a neighbouring triangle
+    let outline:Vec<(usize,usize)> = edges.iter()
+        .filter(|e| !unique.contains(&(e.1,e.0)))
+        .copied()
+        .collect();
+    //each point on the outline should start exactly one edge, otherwise the outline touches itself
+    let mut next:HashMap<usize,usize> = HashMap::with_capacity(outline.len());
+    for edge in outline.iter(){
+        if next.insert(edge.0, edge.1).is_some(){
+            return None;
+        }
+    }
+    //follow edges around from the first, if that doesnt use every edge there is more than one loop
+    let start = outline.first()?.0;
+    let mut polygon = vec![start];
+    let mut current = next[&start];
+    while current != start{
+        polygon.push(current);
+        current = *next.get(&current)?;
+    }
+    (polygon.len() == outline.len()).then_some(polygon)
+}
+
+//area of a polygon, positive if counter clockwise, negative if clockwise
+pub fn signed_area(points:&[glm::Vec2])->f32{
+    points.iter().zip(points.iter().cycle().skip(1))
+        .map(|(a,b)| a.x*b.y-b.x*a.y)
+        .sum::<f32>()*0.5
+}
+
+//orientation of the map on the sphere
+pub struct MapFrame{
+    pub north: glm::Vec3,
+    //direction of 0 longitude on the equator
+    pub meridian: glm::Vec3,
+    //direction of 90 degrees longitude on the equator
+    pub east: glm::Vec3,
+}
+impl MapFrame{
+    pub fn new(north:&glm::Vec3)->MapFrame{
+        let north = north.normalize();
+        //any direction not too close to north will do as the meridian, once made perpendicular
+        let reference = if north.x.abs()<0.9 {glm::Vec3::x()} else {glm::Vec3::z()};
+        let meridian = (reference-north*glm::dot(&reference,&north)).normalize();
+        let east = glm::cross(&north,&meridian);
+        MapFrame { north, meridian, east }
+    }
+
+    //latitude and longitude of a point, in radians
+    pub fn lat_long(&self, point:&glm::Vec3)->(f32,f32){
+        (glm::dot(point,&self.north).clamp(-1.0, 1.0).asin(),
+            f32::atan2(glm::dot(point,&self.east), glm::dot(point,&self.meridian)))
+    }
+
+    //unit vector at latitude and longitude
+    pub fn direction(&self, lat:f32, long:f32)->glm::Vec3{
+        self.north*lat.sin() + (self.meridian*long.cos()+self.east*long.sin())*lat.cos()
+    }
+}
+
+//orthonormal frame with its first axis through a and its second towards b, as columns
+//rotation taking one pair of points to another is the frame of the second times the transpose of the first
+pub fn edge_frame(a:&glm::Vec3, b:&glm::Vec3)->glm::Mat3{
+    let u = a.normalize();
+    let v = (b-u*glm::dot(&u,b)).normalize();
+    glm::Mat3::from_columns(&[u, v, glm::cross(&u,&v)])
+}
+
+//whether a point on a sphere is inside a triangle on it, wound counter clockwise when seen from outside
+//points on an edge count as inside
+pub fn in_spherical_triangle(tri:&[glm::Vec3], point:&glm::Vec3)->bool{
+    (0..3).all(|i| glm::dot(&glm::cross(&tri[i],&tri[(i+1)%3]),point) >= 0.0)
+}
+
+//distance between two points on the unit sphere along its surface, in radians
+//atan2 rather than acos so it stays accurate for points close together
+pub fn great_circle_distance(a:&glm::Vec3, b:&glm::Vec3)->f32{
+    f32::atan2(glm::cross(a,b).magnitude(), glm::dot(a,b))
+}
 
 //takes surrounding triangles and a target point, returns new traingles all connecting surrounding edges to target
 pub fn connect_point(tris:Vec<u32>, target: u32)->Vec<u32>{
@@ -164,18 +254,53 @@ pub fn connect_point(tris:Vec<u32>, target: u32)->Vec<u32>{
             !edges.iter()
                 .any(|e| edge == &&(e.1,e.0))
         })
-        .map(|e| *e)
+        .copied()
         .collect();
     
     //return, connecting each valid edge to target
     edges.iter()
-        .map(|edge|  vec![edge.0 as u32,edge.1 as u32,target])
-        .flatten()
+        .flat_map(|edge|  vec![edge.0 as u32,edge.1 as u32,target])
         .collect()
 }
 
+//triangulates a counter clockwise polygon by cutting off corners, sharpest first as leaving them would only make them thinner
+//polygon indexes into points, returns none if it cant be triangulated e.g. if it crosses itself
+pub fn ear_clip(points:&[glm::Vec2], polygon:Vec<usize>)->Option<Vec<u32>>{
+    //how much the threshold is raised by each time no corners sharp enough are left
+    const STEP:f32 = std::f32::consts::PI/6.0;
+    let mut remaining = polygon;
+    let mut triangles:Vec<u32> = Vec::with_capacity(remaining.len().saturating_sub(2)*3);
+    let mut threshold = STEP;
+    while remaining.len() > 3{
+        let corners:Vec<glm::Vec2> = remaining.iter().map(|i| points[*i]).collect();
+        let clipped = tris_at_threshold(&corners, remaining.clone(), threshold);
+        if clipped.is_empty(){
+            //every convex corner is under pi, so if none are clipped by then none can be
+            if threshold >= std::f32::consts::PI{
+                return None;
+            }
+            threshold = f32::min(threshold+STEP, std::f32::consts::PI);
+            continue;
+        }
+        //middle point of every clipped corner is no longer on the polygon
+        let middles:HashSet<u32> = clipped.chunks(3).map(|t| t[1]).collect();
+        remaining.retain(|i| !middles.contains(&(*i as u32)));
+        triangles.extend(clipped);
+    }
+    match remaining.len(){
+        //both halves of a quad were clipped at once, so there is nothing left
+        2=> Some(triangles),
+        //whats left should be the last triangle
+        3 if tri_orientation(points[remaining[0]], points[remaining[1]], points[remaining[2]]) == Orientation::CounterClockwise=> {
+            triangles.extend(remaining.iter().map(|i| *i as u32));
+            Some(triangles)
+        },
+        _=> None,
+    }
+}
+
 //takes a polygon, adds triangles between edges at or less than a specified threshold angle in radians
-pub fn tris_at_threshold(points:&Vec<glm::Vec2>,polygon: Vec<usize>, threshold: f32)->Vec<u32>{
+pub fn tris_at_threshold(points:&[glm::Vec2],polygon: Vec<usize>, threshold: f32)->Vec<u32>{
     //go through every pair of connected edges in polygon, if angle between them inside polygon is less than angle given, add tri
     //do not add triangle if contains any other point in triangle
     let mut triangles:Vec<u32> = Vec::new();
@@ -184,7 +309,7 @@ pub fn tris_at_threshold(points:&Vec<glm::Vec2>,polygon: Vec<usize>, threshold:
     //loop through every two edges/potential triangle
     for i in 0..polygon.len(){
         //indices of points in tri
-        let tri:Vec<usize> = (0..=2).into_iter()
+        let tri:Vec<usize> = (0..=2)
             .map(|x| (i+x)%polygon.len())
             .collect();
         //if tri contains point marked avoid, or if is not counterclockwise (either colinear or clockwise), skip
@@ -199,12 +324,19 @@ pub fn tris_at_threshold(points:&Vec<glm::Vec2>,polygon: Vec<usize>, threshold:
         if btoa.angle(&btoc)>threshold{
             continue;
         }
+        //if any other point of the polygon is inside the tri, it would overlap the rest of the polygon
+        if (0..polygon.len())
+            .filter(|j| !tri.contains(j))
+            .any(|j| (0..3).all(|k| 
+                tri_orientation(points[tri[k]], points[tri[(k+1)%3]], points[j]) != Orientation::Clockwise)){
+            continue;
+        }
         //if new edge doesnt intersect with any other edge in polygon and added tris, add tri
-        if !polygon.iter().zip(polygon.iter().skip(1))
+        if !(0..polygon.len()).map(|j| (j,(j+1)%polygon.len()))
             .filter_map(|(a,b)| {
                 //filter out edges that share points with tri
-                if !tri.contains(a) && !tri.contains(b){
-                    Some((points[*a].xy(),points[*b].xy()))
+                if !tri.contains(&a) && !tri.contains(&b){
+                    Some((points[a].xy(),points[b].xy()))
                 }else {
                     None
                 }
@@ -0,0 +1,250 @@
+//runs the simulation on its own thread, so slow steps like retriangulation dont freeze the ui
+//the planet is owned by the thread and only changed through commands sent to it
+//after every update the thread publishes an immutable snapshot, replacing any the renderer hasnt picked up yet
+use std::{sync::{Arc, Mutex, mpsc::{self, Receiver, Sender}}, thread::{self, JoinHandle}, time::{Duration, Instant}};
+
+//internal modules
+use super::{Planet, PLANET_RADIUS, SimInfo, boundary::{self, BoundaryData}, crust::CrustData, events::EventKind, scheduler::Scheduler, surface::CellData, utils::MapFrame};
+
+//how often the thread updates the sim, roughly once a frame
+const TICK: Duration = Duration::from_nanos(16_666_667);
+
+//everything needed to draw the planet and show its settings, as it was at one point in time
+pub struct Snapshot{
+    pub cells: Vec<CellData>,
+    pub crust: Vec<CrustData>,
+    pub boundaries: Vec<BoundaryData>,
+    //every face slot of the mesh, so faces that havent changed stay in the same place
+    pub triangles: Vec<u32>,
+    pub sim_info: SimInfo,
+    pub scheduler: Scheduler,
+    pub plate_seed: u64,
+    pub plates: Vec<PlateMotion>,
+    //newest first
+    pub events: Vec<EventReport>,
+    //whether the sim is paused
+    pub paused: bool,
+}
+impl Snapshot{
+    pub fn new(planet:&Planet, paused:bool)->Snapshot{
+        Snapshot{
+            cells: planet.surface.cells.iter().map(|c| c.contents).collect(),
+            crust: planet.surface.cells.iter().map(|c| c.crust.data()).collect(),
+            boundaries: boundary::boundary_data(planet.surface.cells.len(), &planet.surface.boundaries),
+            triangles: planet.surface.mesh.slots(),
+            sim_info: planet.sim_info.clone(),
+            scheduler: planet.scheduler.clone(),
+            plate_seed: planet.surface.plate_seed,
+            plates: PlateMotion::all(planet),
+            events: EventReport::all(planet),
+            paused,
+        }
+    }
+}
+
+//how a plate is moving, for showing in the ui
+#[derive(Clone, Copy)]
+pub struct PlateMotion{
+    //latitude and longitude of the euler pole the plate turns around, in degrees relative to the planets axis
+    pub pole: (f32,f32),
+    //speed of the plate 90 degrees from its pole, in cm per year
+    pub velocity: f32,
+    //live cells in the plate
+    pub cells: usize,
+}
+impl PlateMotion{
+    pub fn all(planet:&Planet)->Vec<PlateMotion>{
+        let surface = &planet.surface;
+        let frame = MapFrame::new(&planet.sim_info.axis);
+        let mut motions:Vec<PlateMotion> = surface.plates.iter()
+            .map(|plate| {
+                let (lat,long) = frame.lat_long(&plate.axis);
+                PlateMotion { pole: (lat.to_degrees(),long.to_degrees()), velocity: plate.speed*PLANET_RADIUS*100.0, cells: 0 }
+            })
+            .collect();
+        for (i,cell) in surface.cells.iter().enumerate(){
+            if let (Some(p),false) = (cell.plate,surface.bank.contains(&i)){
+                motions[p].cells += 1;
+            }
+        }
+        motions
+    }
+}
+
+//an event from the log, placed by latitude and longitude for showing in the ui
+#[derive(Clone, Copy)]
+pub struct EventReport{
+    pub kind: EventKind,
+    //in degrees relative to the planets axis
+    pub location: (f32,f32),
+    pub magnitude: f32,
+    //years since the planet formed
+    pub time: f64,
+}
+impl EventReport{
+    //every event in the log, newest first
+    pub fn all(planet:&Planet)->Vec<EventReport>{
+        let frame = MapFrame::new(&planet.sim_info.axis);
+        planet.surface.events.iter()
+            .rev()
+            .map(|event| {
+                let (lat,long) = frame.lat_long(&event.position);
+                EventReport { kind: event.kind, location: (lat.to_degrees(),long.to_degrees()), magnitude: event.magnitude, time: event.time }
+            })
+            .collect()
+    }
+}
+
+//change to the planet, returns a message to show the user if it has one
+pub type Edit = Box<dyn FnOnce(&mut Planet)->Option<String> + Send>;
+
+//things the ui can tell the sim thread to do
+pub enum Command{
+    Pause,
+    Resume,
+    //advance by one tectonics step, regardless of being paused
+    Step,
+    //make any other change to the planet, e.g. settings, saving and loading
+    Edit(Edit),
+    Stop,
+}
+
+//handle to the sim thread
+pub struct SimWorker{
+    commands: Sender<Command>,
+    //messages from edits, e.g. if a save worked
+    messages: Receiver<String>,
+    //newest snapshot not yet taken by the renderer
+    published: Arc<Mutex<Option<Arc<Snapshot>>>>,
+    //newest snapshot taken, used by the ui
+    latest: Arc<Snapshot>,
+    thread: Option<JoinHandle<()>>,
+    //why the sim thread stopped, if it stopped without being told to
+    error: Option<String>,
+}
+impl SimWorker{
+    //moves planet onto a new thread and starts simulating it
+    pub fn spawn(planet:Planet)->SimWorker{
+        let (commands, command_rx) = mpsc::channel();
+        let (message_tx, messages) = mpsc::channel();
+        let latest = Arc::new(Snapshot::new(&planet, false));
+        let published = Arc::new(Mutex::new(None));
+        let thread_published = Arc::clone(&published);
+        let thread = thread::spawn(move || run(planet, command_rx, message_tx, thread_published));
+        SimWorker { commands, messages, published, latest, thread: Some(thread), error: None }
+    }
+
+    pub fn send(&self, command:Command){
+        //if the thread has gone there is nothing to tell
+        let _ = self.commands.send(command);
+    }
+
+    //shorthand for sending an edit
+    pub fn edit(&self, edit:impl FnOnce(&mut Planet)->Option<String> + Send + 'static){
+        self.send(Command::Edit(Box::new(edit)));
+    }
+
+    //takes the newest snapshot if there is one the renderer hasnt seen
+    pub fn poll(&mut self)->Option<Arc<Snapshot>>{
+        self.check_thread();
+        let snapshot = self.published.lock().unwrap().take()?;
+        self.latest = Arc::clone(&snapshot);
+        Some(snapshot)
+    }
+
+    //newest snapshot taken by poll
+    pub fn latest(&self)->&Snapshot{
+        &self.latest
+    }
+
+    //next message from an edit, if any
+    pub fn message(&self)->Option<String>{
+        self.messages.try_recv().ok()
+    }
+
+    //why the sim stopped, if it has crashed, nothing sent to it will do anything after this
+    pub fn error(&self)->Option<&str>{
+        self.error.as_deref()
+    }
+
+    //records why the thread stopped if it has finished on its own, which only happens if it panicked
+    fn check_thread(&mut self){
+        if !self.thread.as_ref().is_some_and(|t| t.is_finished()){
+            return;
+        }
+        let reason = match self.thread.take().unwrap().join(){
+            Ok(())=> String::from("stopped unexpectedly"),
+            Err(payload)=> payload.downcast_ref::<&str>().map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| String::from("panicked")),
+        };
+        self.error = Some(reason);
+    }
+}
+impl Drop for SimWorker{
+    fn drop(&mut self){
+        self.send(Command::Stop);
+        if let Some(thread) = self.thread.take(){
+            let _ = thread.join();
+        }
+    }
+}
+
+//the sim thread itself
+fn run(mut planet:Planet, commands:Receiver<Command>, messages:Sender<String>, published:Arc<Mutex<Option<Arc<Snapshot>>>>){
+    let mut paused = false;
+    let mut last_tick = Instant::now();
+    loop{
+        //while paused nothing happens until told, otherwise just take whatever has been sent
+        let mut received:Vec<Command> = Vec::new();
+        if paused{
+            match commands.recv(){
+                Ok(command)=> received.push(command),
+                Err(_)=> return,
+            }
+        }
+        received.extend(commands.try_iter());
+
+        let mut stepped = false;
+        for command in received{
+            match command{
+                Command::Pause=> paused = true,
+                Command::Resume=> {
+                    paused = false;
+                    //time spent paused shouldnt be simulated
+                    last_tick = Instant::now();
+                },
+                Command::Step=> {
+                    planet.advance(planet.scheduler.tectonics.step as f64);
+                    stepped = true;
+                },
+                Command::Edit(edit)=> if let Some(message) = edit(&mut planet){
+                    let _ = messages.send(message);
+                },
+                Command::Stop=> return,
+            }
+        }
+
+        if paused{
+            //no time passes, but changes to settings should still show
+            if !stepped{
+                planet.advance(0.0);
+            }
+        }else{
+            let deltatime = last_tick.elapsed().as_secs_f32();
+            last_tick = Instant::now();
+            planet.update(deltatime);
+        }
+
+        //replace whatever is waiting with the new state
+        let snapshot = Arc::new(Snapshot::new(&planet, paused));
+        *published.lock().unwrap() = Some(snapshot);
+
+        //dont run faster than needed
+        if !paused{
+            if let Some(rest) = TICK.checked_sub(last_tick.elapsed()){
+                thread::sleep(rest);
+            }
+        }
+    }
+}
@@ -1,9 +1,25 @@
+use std::collections::HashSet;
 
 //external crates
 use nalgebra_glm as glm;
+use rand::Rng;
 
 //internal modules
-use crate::planet::utils::{self, *};
+use crate::{graphics::{halfedge::HalfEdgeMesh, render, shapes::Shape}, planet::{self, utils, Planet, adjacency::Adjacency, boundary::BoundaryType, crust::{self, Crust}, systems, events::{self, EventKind, VolcanoKind}, surface::Hotspot, export::{CellField, mesh::SurfaceMesh, raster::{RasterFormat, RasterSampler}}, worker::{Command, SimWorker}}};
+
+//small planet for tests that need to run the sim itself
+fn test_gen()->planet::GenInfo{
+    planet::GenInfo{
+        iterations: 3,
+        seed: 1,
+        plate_no: 4,
+        hotspot_no: 3,
+        hotspot_strength: 2.0,
+        axial_tilt: 23.0/180.0,
+        lapse_rate:9.8,
+        base_temp: 30.0
+    }
+}
 
 //testing the connect_point function, should return 
 #[test]
@@ -47,3 +63,809 @@ fn circumcenter_correct_location(){
     assert_eq!(result_b,expected_b);
 }
 
+
+//the sim systems should run with no display or gl context at all
+#[test]
+fn headless_simulation_runs(){
+    let mut planet = Planet::new(&test_gen());
+    //step far enough that the boundaries get retriangulated a few times
+    for _ in 0..5{
+        planet.step(planet.sim_info.triangulation_interval*2.0);
+    }
+    //mesh should still be made of whole triangles all pointing to real cells
+    assert_eq!(planet.surface.mesh.indices().len()%3, 0);
+    assert!(planet.surface.mesh.indices().iter().all(|i| (*i as usize) < planet.surface.cells.len()));
+    //temperature system should have run on every cell
+    assert!(planet.surface.cells.iter().all(|c| c.contents.temperature.is_finite()));
+}
+
+//two planets from the same seed stepped the same way should be identical
+#[test]
+fn same_seed_same_planet(){
+    let mut a = Planet::new(&test_gen());
+    let mut b = Planet::new(&test_gen());
+    for _ in 0..5{
+        a.step(a.sim_info.triangulation_interval*2.0);
+        b.step(b.sim_info.triangulation_interval*2.0);
+    }
+    assert_eq!(a.surface.mesh.indices(), b.surface.mesh.indices());
+    assert!(a.surface.cells.iter().zip(b.surface.cells.iter())
+        .all(|(x,y)| x.plate == y.plate && x.position == y.position));
+    assert!(a.surface.plates.iter().zip(b.surface.plates.iter())
+        .all(|(x,y)| x.axis == y.axis && x.speed == y.speed));
+}
+
+//reseeding plates should change plates but leave the terrain alone
+#[test]
+fn reseed_plates_keeps_terrain(){
+    let mut planet = Planet::new(&test_gen());
+    let heights:Vec<f32> = planet.surface.cells.iter().map(|c| c.contents.height).collect();
+    let axis = planet.surface.plates[0].axis;
+    planet.surface.reseed_plates(99);
+    assert!(planet.surface.cells.iter().zip(heights).all(|(c,h)| c.contents.height == h));
+    assert!(planet.surface.plates[0].axis != axis);
+    assert!(planet.surface.cells.iter().all(|c| c.plate.is_some()));
+}
+
+//saving then loading should give back the exact same planet, that carries on simulating the same
+#[test]
+fn save_load_round_trip(){
+    let mut planet = Planet::new(&test_gen());
+    planet.step(planet.sim_info.triangulation_interval*2.0);
+    planet.step(planet.sim_info.triangulation_interval*2.0);
+
+    let mut bytes:Vec<u8> = Vec::new();
+    planet.write_to(&mut bytes).unwrap();
+    let mut loaded = Planet::read_from(&mut bytes.as_slice()).unwrap();
+
+    //writing the loaded planet should give identical bytes
+    let mut reloaded_bytes:Vec<u8> = Vec::new();
+    loaded.write_to(&mut reloaded_bytes).unwrap();
+    assert_eq!(bytes, reloaded_bytes);
+
+    //the rng should carry on from where it was saved
+    let draws = |planet:&Planet| {
+        let mut rng = planet.surface.rng.clone();
+        (0..8).map(|_| rng.gen::<u64>()).collect::<Vec<u64>>()
+    };
+    assert_eq!(draws(&planet), draws(&loaded));
+
+    //so both should evolve identically
+    for _ in 0..3{
+        planet.step(planet.sim_info.triangulation_interval*2.0);
+        loaded.step(loaded.sim_info.triangulation_interval*2.0);
+    }
+    assert_eq!(planet.surface.mesh.indices(), loaded.surface.mesh.indices());
+
+    //files that arent saves should be rejected
+    assert!(Planet::read_from(&mut &b"not a planet file"[..]).is_err());
+}
+
+//a field that is the same everywhere should resample to the same value everywhere, with no gaps
+#[test]
+fn raster_resamples_whole_sphere(){
+    let mut planet = Planet::new(&test_gen());
+    planet.surface.cells.iter_mut().for_each(|c| c.contents.height = 2.5);
+    let sampler = RasterSampler::new(&planet.surface, &planet.sim_info.axis, 64, 32);
+    let raster = sampler.sample(&planet.surface, CellField::Height);
+    assert_eq!(raster.values.len(), 64*32);
+    assert!(raster.values.iter().all(|v| (v-2.5).abs() < 1e-4));
+
+    //pgm should be header followed by 2 bytes per pixel
+    let mut bytes:Vec<u8> = Vec::new();
+    raster.write(&mut bytes, RasterFormat::Pgm, CellField::Height.range()).unwrap();
+    let header = b"P5\n64 32\n65535\n";
+    assert_eq!(&bytes[..header.len()], header);
+    assert_eq!(bytes.len(), header.len()+64*32*2);
+}
+
+//a field that rises linearly towards the north pole should be interpolated to match it, across triangles and at the poles
+#[test]
+fn raster_interpolates_linear_field(){
+    let mut planet = Planet::new(&test_gen());
+    let axis = planet.sim_info.axis;
+    planet.surface.cells.iter_mut().for_each(|c| c.contents.height = glm::dot(&c.position.normalize(), &axis));
+    let sampler = RasterSampler::new(&planet.surface, &axis, 64, 32);
+    let raster = sampler.sample(&planet.surface, CellField::Height);
+    for (i,value) in raster.values.iter().enumerate(){
+        let lat = std::f32::consts::FRAC_PI_2-((i/64) as f32+0.5)/32.0*std::f32::consts::PI;
+        //triangles are flat, so sit a little inside the sphere
+        assert!((value-lat.sin()).abs() < 0.02, "{} at latitude {}", value, lat.to_degrees());
+    }
+}
+
+//exported mesh should only have the cells in use, and one face per triangle
+#[test]
+fn mesh_export_skips_banked_cells(){
+    let mut planet = Planet::new(&test_gen());
+    //take a cell out of the mesh as if it was subducted, connecting its neighbours to one of them
+    let neighbour = planet.surface.mesh.faces()
+        .find(|t| t[0] == 0)
+        .map(|t| t[1] as usize)
+        .unwrap();
+    planet.surface.remove_cell(0, neighbour);
+    planet.surface.bank.insert(0);
+    let mesh = SurfaceMesh::new(&planet.surface, 0.01);
+    assert_eq!(mesh.positions.len(), planet.surface.cells.len()-1);
+    assert!(!mesh.cells.contains(&0));
+
+    let mut obj:Vec<u8> = Vec::new();
+    mesh.write_obj(&mut obj).unwrap();
+    let obj = String::from_utf8(obj).unwrap();
+    assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), mesh.positions.len());
+    assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), planet.surface.mesh.face_count());
+}
+
+//the same simulated time should give the same planet whatever the frame rate
+#[test]
+fn fixed_steps_independent_of_frame_rate(){
+    let mut slow = Planet::new(&test_gen());
+    let mut fast = Planet::new(&test_gen());
+    for planet in [&mut slow, &mut fast]{
+        planet.sim_info.years_per_second = 10_000.0;
+        //coarse enough to keep the test quick
+        planet.scheduler.temperature.step = 250.0;
+        planet.scheduler.temperature.max_steps = 100;
+    }
+    //10 seconds at 2 and 8 frames per second
+    for _ in 0..20{
+        slow.update(0.5);
+    }
+    for _ in 0..80{
+        fast.update(0.125);
+    }
+    assert_eq!(slow.scheduler.tectonics.simulated, fast.scheduler.tectonics.simulated);
+    assert_eq!(slow.surface.mesh.indices(), fast.surface.mesh.indices());
+    assert!(slow.surface.cells.iter().zip(fast.surface.cells.iter()).all(|(a,b)| a.position == b.position));
+    assert!(slow.scheduler.behind().is_empty());
+
+    //a huge frame should be capped and reported
+    slow.update(1000.0);
+    assert_eq!(slow.scheduler.behind(), vec!["Tectonics", "Temperature", "Isostasy", "Erosion"]);
+}
+
+//sim thread should follow commands and publish what it did as snapshots
+#[test]
+fn worker_commands_and_snapshots(){
+    let mut worker = SimWorker::spawn(Planet::new(&test_gen()));
+    worker.send(Command::Pause);
+    worker.send(Command::Step);
+    worker.edit(|planet| {
+        planet.sim_info.base_temp = 12.0;
+        Some(String::from("edited"))
+    });
+
+    //wait for a snapshot showing everything has been done
+    let start = std::time::Instant::now();
+    loop{
+        if let Some(snapshot) = worker.poll(){
+            if snapshot.paused && snapshot.sim_info.base_temp == 12.0{
+                assert_eq!(snapshot.scheduler.tectonics.simulated, snapshot.scheduler.tectonics.step as f64);
+                break;
+            }
+        }
+        assert!(start.elapsed().as_secs() < 10, "sim thread never published the edit");
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    assert_eq!(worker.message(), Some(String::from("edited")));
+
+    //a crash on the sim thread should be reported rather than just stopping the snapshots
+    worker.edit(|_| panic!("broken edit"));
+    let start = std::time::Instant::now();
+    while worker.error().is_none(){
+        worker.poll();
+        assert!(start.elapsed().as_secs() < 10, "sim thread crash was never reported");
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    assert_eq!(worker.error(), Some("broken edit"));
+}
+
+//adjacency of a mesh changed locally as the sim runs should match one built from scratch
+#[test]
+fn adjacency_tracks_mesh(){
+    let mut planet = Planet::new(&test_gen());
+    for _ in 0..5{
+        planet.step(planet.sim_info.triangulation_interval*2.0);
+    }
+    let surface = &planet.surface;
+    let rebuilt_mesh = HalfEdgeMesh::new(surface.cells.len(), &surface.mesh.indices());
+    let rebuilt = Adjacency::new(&rebuilt_mesh);
+    for cell in 0..surface.cells.len(){
+        let kept:Vec<usize> = surface.adjacency().neighbours(cell).collect();
+        let fresh:Vec<usize> = rebuilt.neighbours(cell).collect();
+        assert_eq!(kept, fresh);
+        assert_eq!(surface.adjacency().triangles(cell), rebuilt.triangles(cell));
+    }
+    //every edge should also be in the index list
+    let edges:HashSet<(usize,usize)> = surface.mesh.faces()
+        .flat_map(|t| (0..3).map(move |i| (t[i].min(t[(i+1)%3]) as usize,t[i].max(t[(i+1)%3]) as usize)))
+        .collect();
+    assert!(surface.adjacency().edges().all(|e| edges.contains(&e)));
+    assert_eq!(surface.adjacency().edges().count(), edges.len());
+}
+
+//local operations on a closed mesh should keep it closed, with every edge shared by two faces
+#[test]
+fn half_edge_local_ops(){
+    let mut mesh = HalfEdgeMesh::from_shape(&Shape::icosahedron());
+    let closed = |mesh:&HalfEdgeMesh| mesh.faces()
+        .all(|t| (0..3).all(|i| mesh.find_edge(t[(i+1)%3],t[i]).is_some()));
+    assert!(closed(&mesh));
+    let [a,b,c] = mesh.faces().next().unwrap();
+    assert_eq!(mesh.opposite(a,b), Some(c));
+    let d = mesh.opposite(b,a).unwrap();
+
+    //flipping swaps which corners the edge joins
+    mesh.flip_edge(a,b).unwrap();
+    assert_eq!(mesh.face_count(), 20);
+    assert!(mesh.find_edge(a,b).is_none() && mesh.find_edge(c,d).is_some());
+    assert!(closed(&mesh));
+
+    //splitting adds a vertex joined to both ends and both opposite corners
+    let split = mesh.add_vertex();
+    mesh.split_edge(c,d,split).unwrap();
+    assert_eq!(mesh.face_count(), 22);
+    assert_eq!(mesh.neighbours(split).len(), 4);
+    assert!(closed(&mesh));
+
+    //inserting into a face joins the new vertex to its corners
+    let tri = mesh.vertex_faces(split).next().unwrap();
+    let inserted = mesh.add_vertex();
+    let change = mesh.insert_vertex(tri, inserted).unwrap();
+    assert_eq!((change.removed.len(), change.added.len()), (3, 9));
+    assert_eq!(mesh.face_count(), 24);
+    assert!(closed(&mesh));
+
+    //removing both again should fill their holes with nothing left over
+    let slots = mesh.slots();
+    mesh.remove_vertex(inserted, &mesh.fan(inserted, split));
+    mesh.remove_vertex(split, &mesh.fan(split, c));
+    assert_eq!(mesh.face_count(), 20);
+    assert_eq!(mesh.vertex_faces(split).count()+mesh.vertex_faces(inserted).count(), 0);
+    assert!(closed(&mesh));
+
+    //faces that werent touched stay in their slots, and the removed ones slots get used again
+    let after = mesh.slots();
+    assert!(after.len() <= slots.len());
+    let untouched = |t:&[u32]| !t.contains(&split) && !t.contains(&inserted);
+    for (before,now) in slots.chunks(3).zip(after.chunks(3)).filter(|(before,_)| untouched(before)){
+        assert_eq!(before, now);
+    }
+    let reloaded = HalfEdgeMesh::from_slots(mesh.vertex_count(), &after);
+    assert_eq!(reloaded.slots(), after);
+}
+
+//removing a cell whose hole cant all be seen from the provoking cell should still leave every triangle facing out
+#[test]
+fn removing_cell_never_folds_mesh(){
+    let mut planet = Planet::new(&test_gen());
+    let surface = &mut planet.surface;
+    let around:Vec<u32> = surface.mesh.vertex_faces(0).flatten().collect();
+    let ring = utils::triangles_to_polygon(&around).unwrap();
+    let faces = surface.mesh.face_count();
+    //pull the corner before the one opposite the apex across the line between them, so a fan from the apex would fold over it
+    let center = surface.cells[0].position;
+    let opposite = ring.len()/2;
+    let pulled = center*0.9+(surface.cells[ring[opposite+1]].position-center)*0.1;
+    surface.cells[ring[opposite-1]].position = pulled.normalize();
+    let fan = surface.mesh.fan(0, ring[0] as u32);
+    assert!(!fan.chunks(3).all(|t| surface.faces_outwards(t)));
+
+    surface.remove_cell(0, ring[0]);
+    assert_eq!(surface.mesh.face_count(), faces-2);
+    assert!(surface.mesh.faces().all(|t| surface.faces_outwards(&t)));
+    assert!(surface.mesh.faces().all(|t| (0..3).all(|i| surface.mesh.find_edge(t[(i+1)%3],t[i]).is_some())));
+}
+
+//only changed elements should be uploaded, with nearby changes grouped together
+#[test]
+fn dirty_ranges_cover_changes(){
+    let old:Vec<u32> = (0..1000).collect();
+    let mut new = old.clone();
+    assert!(render::dirty_ranges(&old, &new).is_empty());
+    new[5] = 0;
+    new[10] = 0;
+    new[900] = 0;
+    assert_eq!(render::dirty_ranges(&old, &new), vec![5..11, 900..901]);
+}
+
+//retriangulating plate boundaries should leave a closed mesh, wound consistently, with every cell not banked in it
+#[test]
+fn boundary_retriangulation_keeps_mesh_closed(){
+    let mut planet = Planet::new(&test_gen());
+    for _ in 0..20{
+        planet.step(planet.sim_info.triangulation_interval*2.0);
+    }
+    let surface = &planet.surface;
+    //every edge used once each way, so no holes or overlapping triangles
+    let edges:Vec<(usize,usize)> = utils::indices_to_directed_edges(&surface.mesh.indices());
+    let unique:HashSet<(usize,usize)> = edges.iter().copied().collect();
+    assert_eq!(unique.len(), edges.len());
+    assert!(edges.iter().all(|e| unique.contains(&(e.1,e.0))));
+    //counter clockwise seen from outside, with no flat triangles
+    assert!(surface.mesh.faces().all(|t| {
+        let [a,b,c] = t.map(|i| surface.cells[i as usize].position);
+        glm::dot(&glm::cross(&(b-a),&(c-a)),&(a+b+c)) > 0.0
+    }));
+    //a closed mesh with no handles
+    let in_mesh:HashSet<u32> = surface.mesh.faces().flatten().collect();
+    assert_eq!(in_mesh.len() as i64 - (edges.len()/2) as i64 + surface.mesh.face_count() as i64, 2);
+    assert!((0..surface.cells.len()).all(|c| in_mesh.contains(&(c as u32)) != surface.bank.contains(&c)));
+}
+
+//ear clipping should fully triangulate simple polygons, convex or not, and refuse ones that cross themselves
+#[test]
+fn ear_clip_triangulates_polygons(){
+    //a quad can be finished by clipping both halves at once
+    let quad = vec![glm::vec2(0.0,0.0),glm::vec2(1.0,0.0),glm::vec2(1.0,1.0),glm::vec2(0.0,1.0)];
+    assert_eq!(utils::ear_clip(&quad, vec![0,1,2,3]).map(|t| t.len()), Some(6));
+    //an arrow shape with a reflex corner
+    let arrow = vec![glm::vec2(0.0,0.0),glm::vec2(2.0,1.0),glm::vec2(0.0,2.0),glm::vec2(0.5,1.0)];
+    let triangles = utils::ear_clip(&arrow, vec![0,1,2,3]).unwrap();
+    assert_eq!(triangles.len(), 6);
+    assert!(triangles.chunks(3).all(|t| utils::tri_orientation(arrow[t[0] as usize], arrow[t[1] as usize], arrow[t[2] as usize]) == utils::Orientation::CounterClockwise));
+    //a bowtie has no valid triangulation
+    assert!(utils::ear_clip(&quad, vec![0,1,3,2]).is_none());
+}
+
+//after remeshing, the triangles around plate boundaries should already be delaunay
+#[test]
+fn boundary_remesh_is_delaunay(){
+    let mut planet = Planet::new(&test_gen());
+    for _ in 0..20{
+        planet.step(planet.sim_info.triangulation_interval*2.0);
+    }
+    //last step remeshed, so there should be nothing left to flip
+    assert_eq!(planet.surface.delaunay_boundaries(), 0);
+    //flipping keeps the mesh whole
+    let edges:Vec<(usize,usize)> = utils::indices_to_directed_edges(&planet.surface.mesh.indices());
+    let unique:HashSet<(usize,usize)> = edges.iter().copied().collect();
+    assert_eq!(unique.len(), edges.len());
+    assert!(edges.iter().all(|e| unique.contains(&(e.1,e.0))));
+}
+
+//a sinking cell should drag its own plate into a trench and raise an arc inland on the plate overriding it
+#[test]
+fn subduction_makes_trench_and_arc(){
+    let mut planet = Planet::new(&test_gen());
+    let surface = &mut planet.surface;
+    let neighbours:Vec<usize> = surface.adjacency().neighbours(0).collect();
+    //cell and one neighbour sink, everything else overrides
+    for (i,cell) in surface.cells.iter_mut().enumerate(){
+        cell.plate = Some(if i == 0 || i == neighbours[0] {0} else {1});
+        cell.set_height(-2.0);
+    }
+    surface.remove_cell(0, neighbours[1]);
+    surface.subduct(0, &neighbours);
+    assert_eq!(surface.plates[0].subducted, 1.0);
+    assert!(surface.cells[neighbours[0]].contents.height < -2.0);
+    //ocean crust has nothing to scrape off, so only cells further in are raised
+    let raised:Vec<usize> = (0..surface.cells.len()).filter(|c| surface.cells[*c].contents.height > -2.0).collect();
+    assert!(!raised.is_empty());
+    assert!(raised.iter().all(|c| *c != 0 && !neighbours.contains(c) && surface.cells[*c].plate == Some(1)));
+}
+
+//continental plates meeting should fold crust into a raised belt on both sides, leaving their motion to the collision drag
+#[test]
+fn collision_builds_mountains(){
+    let mut planet = Planet::new(&test_gen());
+    let surface = &mut planet.surface;
+    let neighbours:Vec<usize> = surface.adjacency().neighbours(0).collect();
+    for (i,cell) in surface.cells.iter_mut().enumerate(){
+        cell.plate = Some(if i == 0 || i == neighbours[0] {0} else {1});
+        cell.set_height(1.0);
+        cell.crust = Crust::continental(1.0, 0.0, cell.plate);
+    }
+    let folded = surface.cells[0].crust;
+    let speeds:Vec<f32> = surface.plates.iter().map(|p| p.speed).collect();
+    surface.remove_cell(0, neighbours[1]);
+    surface.collide(0, &neighbours);
+    //nothing sinks, the suture itself is raised the most
+    assert_eq!(surface.plates[0].subducted, 0.0);
+    assert!(neighbours.iter().all(|n| surface.cells[*n].contents.height > 1.4));
+    //its crust is shared between them, on top of the thickening that holds the suture up
+    let mut expected = folded;
+    expected.fold(&folded, 1.0/neighbours.len() as f32);
+    expected.support(systems::SUTURE_UPLIFT);
+    for n in neighbours.iter(){
+        assert_eq!(surface.cells[*n].crust.thickness, expected.thickness);
+    }
+    let belt:Vec<usize> = (0..surface.cells.len())
+        .filter(|c| *c != 0 && !neighbours.contains(c) && surface.cells[*c].contents.height > 1.0)
+        .collect();
+    assert!(!belt.is_empty());
+    assert!(surface.plates.iter().map(|p| p.speed).eq(speeds));
+}
+
+//new crust should form at ridge height, then sink fastest while young and only while under the sea
+#[test]
+fn sea_floor_subsides_with_age(){
+    let mut planet = Planet::new(&test_gen());
+    let surface = &mut planet.surface;
+    let edge = surface.adjacency().edges().next().unwrap();
+    let ridge = surface.ridge_cell(edge, surface.cells[0].position, 0);
+    assert_eq!(ridge.contents.height, crust::RIDGE_HEIGHT);
+    assert_eq!(ridge.crust, Crust::oceanic(0.0, ridge.plate));
+    surface.cells[0] = ridge;
+    surface.cells[1].crust = Crust::oceanic(50_000_000.0, None);
+    surface.cells[1].set_height(crust::ocean_depth(50_000_000.0));
+    surface.cells[2].crust = Crust::continental(1.0, 0.0, None);
+    surface.cells[2].set_height(1.0);
+    let before:Vec<f32> = surface.cells.iter().map(|c| c.contents.height).collect();
+    for _ in 0..10{
+        surface.age_crust(100_000.0);
+    }
+    let young = before[0]-surface.cells[0].contents.height;
+    let old = before[1]-surface.cells[1].contents.height;
+    assert!(young > old && old > 0.0);
+    assert!((surface.cells[0].contents.height-crust::ocean_depth(1_000_000.0)).abs() < 1e-3);
+    assert!(surface.cells[0].crust.density > Crust::oceanic(0.0, None).density);
+    assert_eq!(surface.cells[2].contents.height, 1.0);
+    assert_eq!(surface.cells[2].crust.age, 1_000_000.0);
+}
+
+//generated crust should follow the land, belong to the plate it starts on, and set how dense each plate is
+#[test]
+fn crust_generated_from_terrain(){
+    let planet = Planet::new(&test_gen());
+    let surface = &planet.surface;
+    for cell in surface.cells.iter(){
+        if cell.contents.height >= 0.0{
+            assert!(cell.crust.is_continental());
+        }else if cell.contents.height < crust::RIDGE_HEIGHT{
+            assert!(!cell.crust.is_continental());
+        }
+        assert_eq!(cell.crust.origin, cell.plate);
+    }
+    //continental crust is lighter, so plates with more of it are too
+    let continental = |p:usize| surface.cells.iter().filter(|c| c.plate == Some(p) && c.crust.is_continental()).count() as f32/
+        surface.cells.iter().filter(|c| c.plate == Some(p)).count() as f32;
+    let mut plates:Vec<usize> = (0..surface.plates.len()).collect();
+    plates.sort_by(|a,b| continental(*a).total_cmp(&continental(*b)));
+    assert!(plates.windows(2).all(|p| surface.plates[p[0]].density >= surface.plates[p[1]].density));
+}
+
+//surface should settle where its crust floats, rising where crust is thickened and rebounding when worn down
+#[test]
+fn isostasy_balances_crust(){
+    let mut planet = Planet::new(&test_gen());
+    let surface = &mut planet.surface;
+    //generated continents already float where they are
+    let land = (0..surface.cells.len()).find(|c| surface.cells[*c].crust.is_continental()).unwrap();
+    let height = surface.cells[land].contents.height;
+    assert!((surface.cells[land].crust.equilibrium_height()-height).abs() < 1e-3);
+    surface.isostasy(1_000_000.0);
+    assert!((surface.cells[land].contents.height-height).abs() < 1e-3);
+
+    //thickening the crust raises it
+    surface.cells[land].crust.thickness *= 1.1;
+    surface.isostasy(1_000.0);
+    let raised = surface.cells[land].contents.height;
+    assert!(raised > height);
+    surface.isostasy(1_000_000.0);
+    assert!(surface.cells[land].contents.height > raised);
+
+    //wearing a km off the top takes crust with it, but the rest rebounds most of the way back up
+    let settled = surface.cells[land].contents.height;
+    surface.cells[land].erode(1.0);
+    surface.isostasy(1_000_000.0);
+    let rebound = surface.cells[land].contents.height;
+    assert!(rebound < settled && rebound > settled-0.5);
+
+    //new sea floor floats at the ridge
+    assert!((crust::Crust::oceanic(0.0, None).equilibrium_height()-crust::RIDGE_HEIGHT).abs() < 0.1);
+}
+
+//plumes should stay put as plates move, building volcanoes that sink once they have cooled off the plume
+#[test]
+fn hotspots_build_sinking_volcanoes(){
+    let mut planet = Planet::new(&test_gen());
+    assert_eq!(planet.surface.hotspots.len(), 3);
+    let positions:Vec<glm::Vec3> = planet.surface.hotspots.iter().map(|h| h.position).collect();
+    planet.step(planet.sim_info.triangulation_interval*2.0);
+    assert!(planet.surface.hotspots.iter().zip(positions.iter()).all(|(h,p)| h.position == *p));
+
+    let surface = &mut planet.surface;
+    surface.hotspots = vec![Hotspot { position: surface.cells[0].position, strength: 2.0 }];
+    let before:Vec<f32> = surface.cells.iter().map(|c| c.contents.height).collect();
+    surface.hotspot_volcanism(1_000_000.0);
+    //only cells close to the plume are touched
+    let far:Vec<usize> = (0..surface.cells.len())
+        .filter(|c| (surface.cells[*c].position-surface.cells[0].position).magnitude() > surface.cell_distance*2.0)
+        .collect();
+    assert!(far.iter().all(|c| surface.cells[*c].contents.height == before[*c]));
+    //and every cell close to it is, found by walking out from the cell above it
+    let in_mesh:HashSet<u32> = surface.mesh.faces().flatten().collect();
+    assert!((0..surface.cells.len())
+        .filter(|c| in_mesh.contains(&(*c as u32)) && (surface.cells[*c].position-surface.cells[0].position).magnitude() < surface.cell_distance)
+        .all(|c| surface.cells[c].contents.height > before[c]));
+    assert_eq!(surface.nearest_cell(&surface.cells[0].position), Some(0));
+    surface.isostasy(100_000.0);
+    let built = surface.cells[0].contents.height;
+    assert!(built > before[0] && surface.cells[0].crust.swell > 0.0);
+
+    //carried off the plume, it cools and sinks but still stands above sea floor of the same age
+    surface.hotspots.clear();
+    surface.age_crust(50_000_000.0);
+    surface.isostasy(1_000_000.0);
+    let sunk = surface.cells[0].contents.height;
+    assert!(sunk < built && sunk > crust::ocean_depth(surface.cells[0].crust.age)+1.0);
+}
+
+//splitting should carve a new plate out of one side of an old one, and merging should fold it back with ids kept in order
+#[test]
+fn plates_split_and_merge(){
+    let mut planet = Planet::new(&test_gen());
+    let surface = &mut planet.surface;
+    let plates = surface.plates.len();
+    let before:Vec<Option<usize>> = surface.cells.iter().map(|c| c.plate).collect();
+    let largest = (0..plates).max_by_key(|p| before.iter().filter(|c| **c == Some(*p)).count()).unwrap();
+
+    let sizes = surface.plate_sizes();
+    let (new,moved_count) = surface.split_plate(largest).unwrap();
+    assert_eq!(new, plates);
+    let moved:Vec<usize> = (0..before.len()).filter(|c| surface.cells[*c].plate == Some(new)).collect();
+    assert!(!moved.is_empty());
+    //sizes can be kept up to date from what moved instead of counted again
+    assert_eq!(moved.len(), moved_count);
+    assert_eq!(surface.plate_sizes(), [&sizes[..largest],&[sizes[largest]-moved_count],&sizes[largest+1..],&[moved_count]].concat());
+    assert!(moved.iter().all(|c| before[*c] == Some(largest)));
+    //the old plate keeps the bigger side
+    let kept = surface.cells.iter().filter(|c| c.plate == Some(largest)).count();
+    assert!(kept >= moved.len());
+    //crust remembers where it formed
+    assert!(moved.iter().all(|c| surface.cells[*c].crust.origin == Some(largest)));
+
+    surface.merge_plates(largest, new);
+    assert_eq!(surface.plates.len(), plates);
+    assert!(surface.cells.iter().zip(before.iter()).all(|(c,p)| c.plate == *p));
+
+    //removing a plate from the middle shifts the ones after it down
+    surface.merge_plates(1, 0);
+    assert_eq!(surface.plates.len(), plates-1);
+    for (cell,plate) in surface.cells.iter().zip(before.iter()){
+        let expected = plate.map(|p| p.saturating_sub(1));
+        assert_eq!(cell.plate, expected);
+        assert_eq!(cell.crust.origin, expected);
+    }
+}
+
+//plates at rest should be set moving by the forces at their edges, and continents pushed together should slow each other more than sinking sea floor
+//boundaries with no motion across them at all shouldnt push or pull
+#[test]
+fn plates_driven_by_boundary_forces(){
+    let mut planet = Planet::new(&test_gen());
+    //boundaries from the starting motion
+    planet.surface.classify_boundaries();
+    for plate in planet.surface.plates.iter_mut(){
+        plate.speed = 0.0;
+    }
+    planet.surface.plate_motion(10_000_000.0);
+    assert!(planet.surface.plates.iter().all(|p| p.speed > 0.0));
+
+    //nothing moving, so nothing to drive plates
+    for plate in planet.surface.plates.iter_mut(){
+        plate.speed = 0.0;
+    }
+    planet.surface.classify_boundaries();
+    assert!(planet.surface.boundaries.iter().all(|e| e.kind == BoundaryType::Transform));
+    planet.surface.plate_motion(10_000_000.0);
+    assert!(planet.surface.plates.iter().all(|p| p.speed == 0.0));
+
+    //same starting motion, with only the crust changed
+    let total_speed = |continental:bool| {
+        let mut planet = Planet::new(&test_gen());
+        for cell in planet.surface.cells.iter_mut(){
+            cell.crust = if continental {Crust::continental(0.5, 0.0, cell.plate)} else {Crust::oceanic(crust::SUBSIDENCE_AGE, cell.plate)};
+        }
+        planet.surface.plate_motion(10_000_000.0);
+        planet.surface.plates.iter().map(|p| p.speed).sum::<f32>()
+    };
+    assert!(total_speed(true) < total_speed(false));
+}
+
+//boundary edges should be exactly the edges between plates, and running every plate backwards should swap convergent and divergent
+#[test]
+fn boundaries_classified_by_relative_motion(){
+    let mut planet = Planet::new(&test_gen());
+    let surface = &mut planet.surface;
+    surface.classify_boundaries();
+    let between_plates = surface.adjacency().edges()
+        .filter(|(a,b)| surface.cells[*a].plate != surface.cells[*b].plate)
+        .count();
+    assert_eq!(surface.boundaries.len(), between_plates);
+    assert!(surface.boundaries.iter().any(|e| e.kind == BoundaryType::Convergent));
+    assert!(surface.boundaries.iter().any(|e| e.kind == BoundaryType::Divergent));
+
+    let before = surface.boundaries.clone();
+    for plate in surface.plates.iter_mut(){
+        plate.axis = -plate.axis;
+    }
+    surface.classify_boundaries();
+    for (old,new) in before.iter().zip(surface.boundaries.iter()){
+        assert_eq!(old.cells, new.cells);
+        let expected = match old.kind{
+            BoundaryType::Convergent=> BoundaryType::Divergent,
+            BoundaryType::Divergent=> BoundaryType::Convergent,
+            BoundaryType::Transform=> BoundaryType::Transform,
+        };
+        assert_eq!(new.kind, expected);
+    }
+}
+
+//a fault loaded past its own limit should break, logging an earthquake and marking the ground around it as hazardous
+//faults should build up strain over several steps, and break several times in a long one
+#[test]
+fn strained_faults_break_as_earthquakes(){
+    let mut planet = Planet::new(&test_gen());
+    let surface = &mut planet.surface;
+    let fault = surface.boundaries.iter()
+        .find(|e| e.kind != BoundaryType::Divergent)
+        .unwrap()
+        .cells.0;
+    //short of its limit it holds, however many times it is checked
+    surface.cells[fault].crust.rupture = 12.0;
+    surface.cells[fault].crust.strain = 11.0;
+    for _ in 0..10{
+        surface.earthquakes(0.0);
+    }
+    assert_eq!(surface.events.iter().count(), 0);
+    assert_eq!(surface.cells[fault].crust.strain, 11.0);
+    surface.cells[fault].crust.strain = 12.0;
+    surface.earthquakes(0.0);
+
+    let quake = *surface.events.iter().last().unwrap();
+    assert_eq!(quake.kind, EventKind::Earthquake);
+    assert!(quake.magnitude > 5.0 && quake.magnitude < 10.0);
+    assert_eq!(surface.cells[fault].crust.strain, 0.0);
+    assert_eq!(surface.cells[fault].crust.hazard, quake.magnitude);
+    //the next break comes at a new limit
+    let limit = surface.cells[fault].crust.rupture;
+    assert!((5.0..15.0).contains(&limit) && limit != 12.0);
+    //hazard fades as the crust ages
+    surface.age_crust(100_000.0);
+    assert!(surface.cells[fault].crust.hazard < quake.magnitude*0.5);
+
+    //a whole step is loaded a bit at a time, so quakes are spread through it
+    let mut planet = Planet::new(&test_gen());
+    let surface = &mut planet.surface;
+    let step = planet.scheduler.tectonics.step as f64;
+    surface.time = step;
+    surface.earthquakes(step as f32);
+    let mut times:Vec<f64> = surface.events.iter().map(|e| e.time).collect();
+    times.dedup();
+    assert!(times.len() > 1 && times.iter().all(|t| *t > 0.0 && *t <= step));
+    //faults that didnt just break keep their strain for next time
+    assert!(surface.cells.iter().any(|c| c.crust.strain > 0.0 && c.crust.strain < c.crust.rupture));
+}
+
+//every kind of volcano should be found, and eruptions should pile rock up, be logged, and cool the planet until their aerosols settle
+#[test]
+fn eruptions_raise_ground_and_cool_the_planet(){
+    let mut planet = Planet::new(&test_gen());
+    let volcanoes = planet.surface.volcanoes();
+    for kind in [VolcanoKind::Arc, VolcanoKind::Rift, VolcanoKind::Hotspot]{
+        assert!(volcanoes.values().any(|k| *k == kind));
+    }
+    planet.surface.temperature(0.0, &planet.sim_info);
+    let before:Vec<(f32,f32)> = planet.surface.cells.iter().map(|c| (c.contents.height,c.contents.temperature)).collect();
+
+    //long enough for every volcano to erupt
+    planet.surface.eruptions(1_000_000_000.0, &mut planet.sim_info);
+    let eruptions = planet.surface.events.iter().filter(|e| matches!(e.kind, EventKind::Eruption(_))).count();
+    assert_eq!(eruptions, volcanoes.len().min(events::LOG_SIZE));
+    //however many earthquakes there are, they only push out older earthquakes
+    planet.surface.earthquakes(planet.scheduler.tectonics.step);
+    let count = |kind:fn(&EventKind)->bool| planet.surface.events.iter().filter(|e| kind(&e.kind)).count();
+    assert_eq!(count(|k| *k == EventKind::Earthquake), events::LOG_SIZE);
+    assert_eq!(count(|k| matches!(k, EventKind::Eruption(_))), eruptions);
+    for cell in volcanoes.keys(){
+        assert!(planet.surface.cells[*cell].contents.height > before[*cell].0);
+        assert!(planet.surface.cells[*cell].crust.eruption >= 4.0);
+    }
+    assert!(planet.sim_info.aerosols > 0.0);
+    //a big eruption's worth of aerosols cools everywhere
+    planet.sim_info.aerosols = 1.0;
+    planet.surface.temperature(0.0, &planet.sim_info);
+    for (cell,(_,temperature)) in planet.surface.cells.iter().zip(before.iter()){
+        assert!(cell.contents.temperature < *temperature);
+    }
+
+    //they last through steps shorter than they do, and a few years later the skies have cleared
+    planet.surface.eruptions(0.1, &mut planet.sim_info);
+    assert!(planet.sim_info.aerosols > 0.5);
+    planet.surface.eruptions(10.0, &mut planet.sim_info);
+    assert!(planet.sim_info.aerosols < 0.001);
+}
+
+//the same eruptions should leave the same aerosols whether they are run in many short frames or a few long ones
+#[test]
+fn aerosols_match_across_frame_rates(){
+    let mut short_frames = Planet::new(&test_gen());
+    let mut long_frames = Planet::new(&test_gen());
+    let step = short_frames.scheduler.tectonics.step as f64;
+    for _ in 0..16{
+        short_frames.advance(step);
+    }
+    long_frames.advance(step*8.0);
+    long_frames.advance(step*8.0);
+    let eruptions = |planet:&Planet| planet.surface.events.iter().filter(|e| matches!(e.kind, EventKind::Eruption(_))).count();
+    assert!(eruptions(&short_frames) > 0);
+    assert_eq!(eruptions(&short_frames), eruptions(&long_frames));
+    assert!(short_frames.sim_info.aerosols > 0.0);
+    assert!((short_frames.sim_info.aerosols-long_frames.sim_info.aerosols).abs() <= short_frames.sim_info.aerosols*1e-4);
+}
+
+//rivers should wear land down and carry it out to sea, moving material without making or losing any
+#[test]
+fn rivers_carry_land_out_to_sea(){
+    let mut planet = Planet::new(&test_gen());
+    //kept clear of the height limits so nothing gets clipped
+    for cell in planet.surface.cells.iter_mut(){
+        let height = cell.contents.height.clamp(-8.0, 8.0);
+        cell.set_height(height);
+    }
+    let before:Vec<f32> = planet.surface.cells.iter().map(|c| c.contents.height).collect();
+
+    //nothing happens if rock cant be worn away
+    planet.sim_info.erodibility = 0.0;
+    planet.surface.hydraulic_erosion(100_000.0, &planet.sim_info);
+    assert!(planet.surface.cells.iter().zip(before.iter()).all(|(c,h)| c.contents.height == *h));
+
+    planet.sim_info.erodibility = planet::DEFAULT_ERODIBILITY;
+    planet.surface.hydraulic_erosion(100_000.0, &planet.sim_info);
+    let (mut land_change,mut sea_change) = (0.0,0.0);
+    for (cell,height) in planet.surface.cells.iter().zip(before.iter()){
+        if *height > 0.0 {land_change += cell.contents.height-height} else {sea_change += cell.contents.height-height}
+    }
+    assert!(land_change < 0.0);
+    assert!(sea_change > 0.0);
+    assert!((land_change+sea_change).abs() < 0.001*land_change.abs());
+}
+
+//slopes past the talus angle should slide until they are just under it, and creep should smooth out gentler ones, without losing any ground
+#[test]
+fn steep_slopes_slide_and_hillslopes_smooth(){
+    let spiked = ||{
+        let mut planet = Planet::new(&test_gen());
+        for cell in planet.surface.cells.iter_mut(){
+            cell.set_height(1.0);
+        }
+        planet.surface.cells[0].set_height(6.0);
+        planet
+    };
+    let total = |planet:&Planet| planet.surface.cells.iter().map(|c| c.contents.height).sum::<f32>();
+
+    //without creep only the part of the spike past the talus angle comes down
+    let mut planet = spiked();
+    let before = total(&planet);
+    planet.sim_info.diffusivity = 0.0;
+    //cells in the test mesh are far apart, so only a gentle angle is steep enough to fail
+    planet.sim_info.talus_angle = 0.1;
+    for _ in 0..200{
+        planet.surface.thermal_erosion(1000.0, &planet.sim_info);
+    }
+    let talus = planet.sim_info.talus_angle.to_radians().tan();
+    let peak = planet.surface.cells[0].contents.height;
+    assert!(peak < 6.0);
+    for n in planet.surface.adjacency().neighbours(0){
+        let cell = &planet.surface.cells[n];
+        let distance = utils::great_circle_distance(&planet.surface.cells[0].position, &cell.position)*planet::PLANET_RADIUS/1000.0;
+        assert!(cell.contents.height > 1.0);
+        assert!(peak-cell.contents.height < distance*talus*1.1);
+    }
+    assert!((total(&planet)-before).abs() < 0.001);
+
+    //with creep even gentle slopes flatten out
+    let mut creep = spiked();
+    creep.sim_info.talus_angle = 45.0;
+    creep.surface.thermal_erosion(1_000_000.0, &creep.sim_info);
+    assert!(creep.surface.cells[0].contents.height < 6.0);
+    assert!((total(&creep)-before).abs() < 0.001);
+
+    //the same start always gives the same result
+    let mut again = spiked();
+    again.sim_info.talus_angle = 45.0;
+    again.surface.thermal_erosion(1_000_000.0, &again.sim_info);
+    assert!(creep.surface.cells.iter().zip(again.surface.cells.iter()).all(|(a,b)| a.contents.height == b.contents.height));
+}